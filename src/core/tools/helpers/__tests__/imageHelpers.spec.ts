@@ -0,0 +1,41 @@
+import { randomBytes } from "crypto"
+
+import { encodeBase64Chunked } from "../imageHelpers"
+
+describe("imageHelpers", () => {
+	describe("encodeBase64Chunked", () => {
+		const collect = (data: Buffer, chunkBytes?: number) => {
+			const chunks: string[] = []
+			encodeBase64Chunked(data, (chunk) => chunks.push(chunk), chunkBytes)
+			return chunks
+		}
+
+		it("should reassemble to the one-shot encoding", () => {
+			const data = randomBytes(1_000_001)
+
+			const chunks = collect(data, 3 * 1024)
+
+			expect(chunks.length).toBe(Math.ceil(data.length / (3 * 1024)))
+			expect(chunks.join("")).toBe(data.toString("base64"))
+		})
+
+		it("should only pad the final chunk", () => {
+			const chunks = collect(Buffer.from("hello world"), 3)
+
+			expect(chunks.slice(0, -1).every((chunk) => !chunk.includes("="))).toBe(true)
+			expect(chunks.join("")).toBe(Buffer.from("hello world").toString("base64"))
+		})
+
+		it("should use a single chunk for small buffers with the default size", () => {
+			expect(collect(Buffer.from("small"))).toEqual([Buffer.from("small").toString("base64")])
+		})
+
+		it("should not call the callback for an empty buffer", () => {
+			expect(collect(Buffer.alloc(0))).toEqual([])
+		})
+
+		it("should reject chunk sizes that are not a multiple of 3", () => {
+			expect(() => collect(Buffer.from("data"), 4)).toThrow(RangeError)
+		})
+	})
+})
@@ -83,6 +83,31 @@ export async function readImageAsDataUrlWithBuffer(filePath: string): Promise<{
 	return { dataUrl, buffer: fileBuffer }
 }
 
+/**
+ * Default number of input bytes per chunk for encodeBase64Chunked (a multiple of 3,
+ * so every chunk except the last encodes without padding)
+ */
+export const DEFAULT_BASE64_CHUNK_BYTES = 3 * 256 * 1024
+
+/**
+ * Encodes a buffer as base64 in chunks, passing each encoded piece to a callback.
+ * Concatenating the chunks yields the same string as buffer.toString("base64"),
+ * without ever holding the full encoded string in memory.
+ */
+export function encodeBase64Chunked(
+	data: Buffer,
+	onChunk: (chunk: string) => void,
+	chunkBytes: number = DEFAULT_BASE64_CHUNK_BYTES,
+): void {
+	if (!Number.isInteger(chunkBytes) || chunkBytes <= 0 || chunkBytes % 3 !== 0) {
+		throw new RangeError(`Invalid chunkBytes: ${chunkBytes}. Chunk size must be a positive multiple of 3.`)
+	}
+
+	for (let offset = 0; offset < data.length; offset += chunkBytes) {
+		onChunk(data.subarray(offset, offset + chunkBytes).toString("base64"))
+	}
+}
+
 /**
  * Checks if a file extension is a supported image format
  */
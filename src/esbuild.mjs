@@ -111,7 +111,7 @@ async function main() {
 	 */
 	const workerConfig = {
 		...buildOptions,
-		entryPoints: ["workers/countTokens.ts"],
+		entryPoints: ["workers/countTokens.ts", "workers/findSpans.ts"],
 		outdir: "dist/workers",
 	}
 
@@ -0,0 +1,62 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import { DEFAULT_BATCH_CONCURRENCY, getBatchConcurrency, setBatchConcurrency } from "../batch-concurrency"
+import { searchDirectorySummary } from "../directory-search"
+import { hashFile, hashFiles } from "../file-stats"
+import { readLineRangesBatch } from "../read-lines"
+import { previewReplaceInFiles } from "../text-transforms"
+
+describe("batch-concurrency", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-batch-concurrency-"))
+	})
+
+	afterEach(async () => {
+		setBatchConcurrency(0)
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	it("should produce the same batch results with a small pool", async () => {
+		const paths = await Promise.all(
+			Array.from({ length: 10 }, async (_, i) => {
+				const filePath = path.join(tmpDir, `file-${i}.txt`)
+				await fs.writeFile(filePath, `match ${i}\n`.repeat(i + 1))
+				return filePath
+			}),
+		)
+
+		const ranges = paths.map((filePath, i) => ({ path: filePath, start: 0, end: i }))
+		const defaultHashes = await hashFiles(paths)
+		const defaultSummary = await searchDirectorySummary(tmpDir, "match")
+		const defaultRanges = await readLineRangesBatch(ranges)
+		const defaultPreviews = await previewReplaceInFiles(paths, "match", "hit")
+
+		setBatchConcurrency(2)
+
+		expect(getBatchConcurrency()).toBe(2)
+		expect(await hashFiles(paths)).toEqual(defaultHashes)
+		expect(await hashFiles(paths)).toEqual(await Promise.all(paths.map((filePath) => hashFile(filePath))))
+		expect(await searchDirectorySummary(tmpDir, "match")).toEqual(defaultSummary)
+		expect(defaultSummary.totalMatches).toBe(55)
+		expect(await readLineRangesBatch(ranges)).toEqual(defaultRanges)
+		expect(defaultRanges[2]).toBe("match 2\nmatch 2\nmatch 2\n")
+		expect(await previewReplaceInFiles(paths, "match", "hit")).toEqual(defaultPreviews)
+		expect(defaultPreviews.map((preview) => preview.changeCount)).toEqual(paths.map((_, i) => i + 1))
+	})
+
+	it("should restore the default with 0", () => {
+		setBatchConcurrency(1)
+		setBatchConcurrency(0)
+
+		expect(getBatchConcurrency()).toBe(DEFAULT_BATCH_CONCURRENCY)
+	})
+
+	it("should reject negative or fractional values", () => {
+		expect(() => setBatchConcurrency(-1)).toThrow(RangeError)
+		expect(() => setBatchConcurrency(1.5)).toThrow(RangeError)
+	})
+})
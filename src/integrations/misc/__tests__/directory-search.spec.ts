@@ -0,0 +1,109 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import { isIgnored, searchDirectorySummary } from "../directory-search"
+
+describe("directory-search", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-directory-search-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	const writeFixture = async (relativePath: string, content: string | Buffer) => {
+		const filePath = path.join(tmpDir, relativePath)
+		await fs.mkdir(path.dirname(filePath), { recursive: true })
+		await fs.writeFile(filePath, content)
+	}
+
+	describe("searchDirectorySummary", () => {
+		beforeEach(async () => {
+			await writeFixture("src/app.ts", "// TODO: one\nconst x = 1 // TODO: two\n")
+			await writeFixture("src/lib/util.ts", "// TODO: three\n")
+			await writeFixture("src/clean.ts", "export {}\n")
+			await writeFixture("README.md", "todo list\n")
+		})
+
+		it("should report per-file and total counts", async () => {
+			expect(await searchDirectorySummary(tmpDir, "TODO")).toEqual({
+				totalMatches: 3,
+				fileCount: 2,
+				files: [
+					{ path: "src/app.ts", count: 2 },
+					{ path: "src/lib/util.ts", count: 1 },
+				],
+			})
+		})
+
+		it("should support case-insensitive matching", async () => {
+			const summary = await searchDirectorySummary(tmpDir, "todo", { caseInsensitive: true })
+
+			expect(summary.totalMatches).toBe(4)
+			expect(summary.files.map((file) => file.path)).toEqual(["README.md", "src/app.ts", "src/lib/util.ts"])
+		})
+
+		it("should skip files excluded by root and nested .gitignore files", async () => {
+			await writeFixture(".gitignore", "dist/\n")
+			await writeFixture("dist/bundle.js", "// TODO: built\n")
+			await writeFixture("src/.gitignore", "lib/\n")
+			await writeFixture("src/generated.ts", "// TODO: generated\n")
+
+			const summary = await searchDirectorySummary(tmpDir, "TODO")
+
+			expect(summary.files.map((file) => file.path)).toEqual(["src/app.ts", "src/generated.ts"])
+		})
+
+		it("should skip binary files and the .git directory", async () => {
+			await writeFixture("image.bin", Buffer.concat([Buffer.from("TODO"), Buffer.alloc(64)]))
+			await writeFixture(".git/COMMIT_EDITMSG", "TODO\n")
+
+			expect((await searchDirectorySummary(tmpDir, "TODO")).fileCount).toBe(2)
+		})
+
+		it("should count matches per line, never across line breaks", async () => {
+			await writeFixture("split.txt", "foo\nbar\r\nfoo bar\n")
+
+			const summary = await searchDirectorySummary(tmpDir, "o\\s+b")
+
+			expect(summary.files).toEqual([{ path: "split.txt", count: 1 }])
+			expect((await searchDirectorySummary(tmpDir, "^bar$")).totalMatches).toBe(1)
+		})
+
+		it("should return an empty summary when nothing matches", async () => {
+			expect(await searchDirectorySummary(tmpDir, "FIXME")).toEqual({ totalMatches: 0, fileCount: 0, files: [] })
+		})
+	})
+
+	describe("isIgnored", () => {
+		beforeEach(async () => {
+			await writeFixture(".gitignore", "target/\n*.log\n")
+			await writeFixture("crates/core/.gitignore", "generated.rs\n!keep.log\n")
+		})
+
+		it("should ignore files under an ignored directory", async () => {
+			expect(await isIgnored(tmpDir, "target/debug/app")).toBe(true)
+			expect(await isIgnored(tmpDir, path.join(tmpDir, "crates/core/target/out.o"))).toBe(true)
+		})
+
+		it("should not ignore files that no rule matches", async () => {
+			expect(await isIgnored(tmpDir, "src/main.rs")).toBe(false)
+			expect(await isIgnored(tmpDir, "targets.txt")).toBe(false)
+		})
+
+		it("should apply nested .gitignore files only within their directory", async () => {
+			expect(await isIgnored(tmpDir, "crates/core/generated.rs")).toBe(true)
+			expect(await isIgnored(tmpDir, "generated.rs")).toBe(false)
+			expect(await isIgnored(tmpDir, "debug.log")).toBe(true)
+			expect(await isIgnored(tmpDir, "crates/core/keep.log")).toBe(false)
+		})
+
+		it("should return false for paths outside the root", async () => {
+			expect(await isIgnored(path.join(tmpDir, "crates"), path.join(tmpDir, "debug.log"))).toBe(false)
+		})
+	})
+})
@@ -1,5 +1,11 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
 import {
 	addLineNumbers,
+	readFileWithLineNumbers,
+	getContentPreview,
 	everyLineHasLineNumbers,
 	stripLineNumbers,
 	truncateOutput,
@@ -709,3 +715,126 @@ describe("processCarriageReturns", () => {
 		expect(processCarriageReturns(input)).toBe(expected)
 	})
 })
+
+describe("readFileWithLineNumbers", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-line-numbers-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	it("should use the same format as addLineNumbers", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, "line 1\nline 2\nline 3\n")
+
+		const result = await readFileWithLineNumbers(filePath)
+
+		expect(result).toBe("1 | line 1\n2 | line 2\n3 | line 3\n")
+		expect(result).toBe(addLineNumbers("line 1\nline 2\nline 3\n"))
+		expect(everyLineHasLineNumbers(result.trimEnd())).toBe(true)
+	})
+
+	it("should pad line numbers to the width of the total line count", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, Array.from({ length: 120 }, (_, i) => `line ${i + 1}`).join("\n"))
+
+		const lines = (await readFileWithLineNumbers(filePath)).trimEnd().split("\n")
+
+		expect(lines).toHaveLength(120)
+		expect(lines[0]).toBe("  1 | line 1")
+		expect(lines[9]).toBe(" 10 | line 10")
+		expect(lines[119]).toBe("120 | line 120")
+		// Every separator lines up in the same column
+		expect(new Set(lines.map((line) => line.indexOf(" | "))).size).toBe(1)
+	})
+
+	it("should keep file line numbers and total-line padding when starting mid-file", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, Array.from({ length: 12 }, (_, i) => `line ${i + 1}`).join("\n"))
+
+		const result = await readFileWithLineNumbers(filePath, 9)
+
+		expect(result).toBe(" 9 | line 9\n10 | line 10\n11 | line 11\n12 | line 12\n")
+	})
+
+	it("should honor an explicit pad width", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, "a\nb")
+
+		expect(await readFileWithLineNumbers(filePath, 1, 4)).toBe("   1 | a\n   2 | b\n")
+	})
+
+	it("should return an empty string for empty files and out-of-range start lines", async () => {
+		const emptyPath = path.join(tmpDir, "empty.txt")
+		await fs.writeFile(emptyPath, "")
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, "a\nb\n")
+
+		expect(await readFileWithLineNumbers(emptyPath)).toBe("")
+		expect(await readFileWithLineNumbers(filePath, 3)).toBe("")
+	})
+
+	it("should reject a pad width that is not a non-negative integer", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, "a\nb\n")
+
+		await expect(readFileWithLineNumbers(filePath, 1, -1)).rejects.toThrow(RangeError)
+		await expect(readFileWithLineNumbers(filePath, 1, 2.5)).rejects.toThrow(RangeError)
+		await expect(readFileWithLineNumbers(filePath, 1, NaN)).rejects.toThrow(RangeError)
+		expect(await readFileWithLineNumbers(filePath, 1, 0)).toBe("1 | a\n2 | b\n")
+	})
+})
+
+describe("getContentPreview", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-content-preview-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	it("should skip a leading block comment license header", async () => {
+		const filePath = path.join(tmpDir, "licensed.ts")
+		await fs.writeFile(
+			filePath,
+			"/*\n * Copyright (c) 2024 Example Corp.\n * Licensed under the MIT License.\n */\n\nimport x from \"x\"\n\nexport const a = 1\nexport const b = 2\n",
+		)
+
+		expect(await getContentPreview(filePath, 3)).toBe('import x from "x"\n\nexport const a = 1')
+	})
+
+	it("should skip a run of line comments containing a license", async () => {
+		const filePath = path.join(tmpDir, "licensed.py")
+		await fs.writeFile(filePath, "# SPDX-License-Identifier: Apache-2.0\n# Some project\n\ndef main():\n    pass\n")
+
+		expect(await getContentPreview(filePath, 2)).toBe("def main():\n    pass")
+	})
+
+	it("should keep a leading comment that is not a license header", async () => {
+		const filePath = path.join(tmpDir, "plain.ts")
+		await fs.writeFile(filePath, "// Helpers for parsing\nexport function parse() {}\n")
+
+		expect(await getContentPreview(filePath, 5)).toBe("// Helpers for parsing\nexport function parse() {}\n")
+	})
+
+	it("should preview from the top when there is no header", async () => {
+		const filePath = path.join(tmpDir, "no-header.txt")
+		await fs.writeFile(filePath, "line 1\r\nline 2\r\nline 3\r\n")
+
+		expect(await getContentPreview(filePath, 2)).toBe("line 1\nline 2")
+	})
+
+	it("should reject a non-positive maxLines", async () => {
+		const filePath = path.join(tmpDir, "file.txt")
+		await fs.writeFile(filePath, "content")
+
+		await expect(getContentPreview(filePath, 0)).rejects.toThrow(RangeError)
+	})
+})
@@ -0,0 +1,367 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+import { createHash, randomBytes } from "crypto"
+import { gzipSync } from "zlib"
+
+import {
+	getByteHistogram,
+	getEntropy,
+	entropyFromHistogram,
+	getLineEndingCounts,
+	detectIndentType,
+	readFileContentWithMeta,
+	getCsvShape,
+	hashFile,
+	hashFiles,
+	isMinified,
+	detectMixedIndentation,
+	readFileContentCached,
+	clearFileCache,
+} from "../file-stats"
+import { detectIndentSize } from "../indentation-reader"
+import { countFileLines } from "../line-counter"
+import { hasTrailingNewline } from "../text-transforms"
+
+describe("file-stats", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-file-stats-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	describe("getByteHistogram", () => {
+		it("should count every byte value", async () => {
+			const filePath = path.join(tmpDir, "bytes.bin")
+			await fs.writeFile(filePath, Buffer.from([0x00, 0x41, 0x41, 0xff, 0x41]))
+
+			const histogram = await getByteHistogram(filePath)
+
+			expect(histogram).toHaveLength(256)
+			expect(histogram[0x00]).toBe(1)
+			expect(histogram[0x41]).toBe(3)
+			expect(histogram[0xff]).toBe(1)
+			expect(histogram.reduce((sum, count) => sum + count, 0)).toBe(5)
+		})
+
+		it("should return all zeros for an empty file", async () => {
+			const filePath = path.join(tmpDir, "empty.bin")
+			await fs.writeFile(filePath, "")
+
+			expect(await getByteHistogram(filePath)).toEqual(new Array(256).fill(0))
+		})
+	})
+
+	describe("getEntropy", () => {
+		it("should report low entropy for text and near 8 bits for compressed data", async () => {
+			const textPath = path.join(tmpDir, "source.ts")
+			await fs.writeFile(textPath, "export function add(a: number, b: number) {\n\treturn a + b\n}\n".repeat(200))
+			const gzipPath = path.join(tmpDir, "archive.gz")
+			await fs.writeFile(gzipPath, gzipSync(randomBytes(64 * 1024)))
+
+			const textEntropy = await getEntropy(textPath)
+			const gzipEntropy = await getEntropy(gzipPath)
+
+			expect(textEntropy).toBeLessThan(5)
+			expect(gzipEntropy).toBeGreaterThan(7.9)
+		})
+
+		it("should be 0 for a single repeated byte and for empty input", () => {
+			const histogram = new Array(256).fill(0)
+			expect(entropyFromHistogram(histogram)).toBe(0)
+
+			histogram[0x20] = 100
+			expect(entropyFromHistogram(histogram)).toBe(0)
+		})
+	})
+
+	describe("getLineEndingCounts", () => {
+		it("should count each terminator style in a mixed file", async () => {
+			const filePath = path.join(tmpDir, "mixed.txt")
+			await fs.writeFile(filePath, "lf\nlf\ncrlf\r\ncr\rlf\ncrlf\r\n\r\rlast")
+
+			expect(await getLineEndingCounts(filePath)).toEqual({ lf: 3, crlf: 2, cr: 3 })
+		})
+
+		it("should count a trailing lone CR", async () => {
+			const filePath = path.join(tmpDir, "trailing-cr.txt")
+			await fs.writeFile(filePath, "a\rb\r")
+
+			expect(await getLineEndingCounts(filePath)).toEqual({ lf: 0, crlf: 0, cr: 2 })
+		})
+
+		it("should count a CRLF split across stream chunks once", async () => {
+			const filePath = path.join(tmpDir, "boundary.txt")
+			// Default stream chunks are 64KB, so the CR ends the first chunk and the LF starts the second
+			await fs.writeFile(filePath, "x".repeat(64 * 1024 - 1) + "\r\n" + "y\n")
+
+			expect(await getLineEndingCounts(filePath)).toEqual({ lf: 1, crlf: 1, cr: 0 })
+		})
+
+		it("should return zero counts for an empty file", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await getLineEndingCounts(filePath)).toEqual({ lf: 0, crlf: 0, cr: 0 })
+		})
+	})
+
+	describe("readFileContentWithMeta", () => {
+		it.each([
+			["tab-indented CRLF", "class A {\r\n\tfoo() {\r\n\t\treturn 1\r\n\t}\r\n}\r\n"],
+			["space-indented LF without a final newline", "def f():\n  if x:\n    return 1\n  return 2"],
+			["mixed endings", "a\nb\r\nc\rd\n"],
+			["unindented", "one\ntwo\n"],
+			["empty", ""],
+		])("should match the single-purpose helpers for a %s file", async (_name, content) => {
+			const filePath = path.join(tmpDir, "file.txt")
+			await fs.writeFile(filePath, content)
+
+			const meta = await readFileContentWithMeta(filePath)
+			const counts = await getLineEndingCounts(filePath)
+
+			expect(meta.content).toBe(await fs.readFile(filePath, "utf8"))
+			expect(meta.lineCount).toBe(await countFileLines(filePath))
+			expect(meta.indentType).toBe(detectIndentType(content))
+			expect(meta.indentSize).toBe(detectIndentSize(content))
+			expect(meta.hasTrailingNewline).toBe(await hasTrailingNewline(filePath))
+			if (meta.lineEnding === "none") {
+				expect(counts).toEqual({ lf: 0, crlf: 0, cr: 0 })
+			} else {
+				expect(counts[meta.lineEnding]).toBe(Math.max(counts.lf, counts.crlf, counts.cr))
+			}
+		})
+
+		it("should report the structure of a tab-indented CRLF file", async () => {
+			const filePath = path.join(tmpDir, "tabs.ts")
+			await fs.writeFile(filePath, "class A {\r\n\tfoo() {\r\n\t\treturn 1\r\n\t}\r\n}\r\n")
+
+			expect(await readFileContentWithMeta(filePath)).toMatchObject({
+				lineCount: 5,
+				indentType: "tabs",
+				lineEnding: "crlf",
+				hasTrailingNewline: true,
+			})
+		})
+	})
+
+	describe("detectIndentType", () => {
+		it("should pick the more common indentation character", () => {
+			expect(detectIndentType("a\n\tb\n\tc\n  d\n")).toBe("tabs")
+			expect(detectIndentType("a\n  b\n    c\n")).toBe("spaces")
+			expect(detectIndentType("a\n\nb\n")).toBe("none")
+		})
+	})
+
+	describe("getCsvShape", () => {
+		it("should not count newlines inside quoted fields as rows", async () => {
+			const filePath = path.join(tmpDir, "quoted.csv")
+			await fs.writeFile(
+				filePath,
+				'id,name,notes\r\n1,"Smith, Jane","line one\r\nline two"\r\n2,Bob,"said ""hi"""\r\n\r\n',
+			)
+
+			expect(await getCsvShape(filePath)).toEqual({ rows: 3, columns: 3, hasInconsistentColumns: false })
+		})
+
+		it("should flag ragged rows", async () => {
+			const filePath = path.join(tmpDir, "ragged.csv")
+			await fs.writeFile(filePath, "a,b,c\n1,2,3\n4,5\n6,7,8,9")
+
+			expect(await getCsvShape(filePath)).toEqual({ rows: 4, columns: 3, hasInconsistentColumns: true })
+		})
+
+		it("should treat a quote inside an unquoted field as literal text", async () => {
+			const filePath = path.join(tmpDir, "inch.csv")
+			await fs.writeFile(filePath, 'item,size,qty\nscreen,27" wide,1\ncable,6",2\n')
+
+			expect(await getCsvShape(filePath)).toEqual({ rows: 3, columns: 3, hasInconsistentColumns: false })
+		})
+
+		it("should support tab-separated files", async () => {
+			const filePath = path.join(tmpDir, "data.tsv")
+			await fs.writeFile(filePath, "a\tb\n1,5\t2\n")
+
+			expect(await getCsvShape(filePath, "\t")).toEqual({ rows: 2, columns: 2, hasInconsistentColumns: false })
+		})
+
+		it("should report an empty file as having no rows", async () => {
+			const filePath = path.join(tmpDir, "empty.csv")
+			await fs.writeFile(filePath, "")
+
+			expect(await getCsvShape(filePath)).toEqual({ rows: 0, columns: 0, hasInconsistentColumns: false })
+		})
+	})
+
+	describe("hashFiles", () => {
+		it("should match individual hashFile calls in input order", async () => {
+			const paths = await Promise.all(
+				["a.bin", "b.txt", "c.txt", "empty"].map(async (name, index) => {
+					const filePath = path.join(tmpDir, name)
+					await fs.writeFile(filePath, index === 3 ? "" : randomBytes(1000 * (index + 1)))
+					return filePath
+				}),
+			)
+
+			const hashes = await hashFiles(paths)
+
+			expect(hashes).toEqual(await Promise.all(paths.map((filePath) => hashFile(filePath))))
+			expect(hashes[3]).toBe(createHash("sha256").digest("hex"))
+		})
+
+		it("should return an empty string for files that can't be read", async () => {
+			const filePath = path.join(tmpDir, "present.txt")
+			await fs.writeFile(filePath, "hello")
+
+			const hashes = await hashFiles([path.join(tmpDir, "missing.txt"), filePath], "md5")
+
+			expect(hashes).toEqual(["", "5d41402abc4b2a76b9719d911017c592"])
+		})
+
+		it("should reject an unknown algorithm", async () => {
+			await expect(hashFiles([], "not-a-hash")).rejects.toThrow()
+		})
+	})
+
+	describe("isMinified", () => {
+		const minifiedJs =
+			"/*! bundle v1.0 */\n" +
+			Array.from({ length: 40 }, (_, i) => `function h${i}(e,t){const n=e.items[${i}]??null;return n}`).join("")
+		const minifiedCss = Array.from({ length: 60 }, (_, i) => `.c${i}{margin:0;padding:${i}px}`).join("")
+		const source = Array.from(
+			{ length: 40 },
+			(_, i) => `function handler${i}(event) {\n\tconst value = event.items[${i}] ?? null\n\treturn value\n}\n`,
+		).join("\n")
+		const prose = Array.from({ length: 5 }, () => "A sentence of ordinary prose. ".repeat(20)).join("\n")
+
+		it.each([
+			["minified JavaScript", minifiedJs, true],
+			["minified CSS", minifiedCss, true],
+			["normal source", source, false],
+			["long-lined prose", prose, false],
+			["an empty file", "", false],
+		])("should classify %s", async (_name, content, expected) => {
+			const filePath = path.join(tmpDir, "file.txt")
+			await fs.writeFile(filePath, content)
+
+			expect(await isMinified(filePath)).toBe(expected)
+		})
+	})
+
+	describe("detectMixedIndentation", () => {
+		it("should report tab-indented lines among space-indented ones", async () => {
+			const filePath = path.join(tmpDir, "mixed.py")
+			await fs.writeFile(
+				filePath,
+				"def f():\n    a = 1\n\tb = 2\n    if a:\n        c = 3\n\t\td = 4\n    return a\n",
+			)
+
+			expect(await detectMixedIndentation(filePath)).toEqual([
+				{ line: 3, type: "minority" },
+				{ line: 6, type: "minority" },
+			])
+		})
+
+		it("should report indentation mixing tabs and spaces", async () => {
+			const filePath = path.join(tmpDir, "mixed.ts")
+			await fs.writeFile(filePath, "class A {\r\n\tfoo() {\r\n\t  bar()\r\n  \tbaz()\r\n\t}\r\n}\r\n")
+
+			expect(await detectMixedIndentation(filePath)).toEqual([
+				{ line: 3, type: "mixed" },
+				{ line: 4, type: "mixed" },
+			])
+		})
+
+		it("should return nothing for consistent indentation", async () => {
+			const filePath = path.join(tmpDir, "clean.ts")
+			await fs.writeFile(filePath, "if (x) {\n  y()\n\n  z()\n}\n")
+
+			expect(await detectMixedIndentation(filePath)).toEqual([])
+		})
+	})
+
+	describe("readFileContentCached", () => {
+		beforeEach(() => {
+			clearFileCache()
+		})
+
+		it("should return cached content while mtime and size are unchanged", async () => {
+			const filePath = path.join(tmpDir, "cached.txt")
+			const mtime = new Date("2024-01-01T00:00:00Z")
+			await fs.writeFile(filePath, "aaaa")
+			await fs.utimes(filePath, mtime, mtime)
+
+			expect(await readFileContentCached(filePath)).toBe("aaaa")
+
+			// Same size and restored mtime: the stale cached content is served
+			await fs.writeFile(filePath, "bbbb")
+			await fs.utimes(filePath, mtime, mtime)
+
+			expect(await readFileContentCached(filePath)).toBe("aaaa")
+		})
+
+		it("should re-read the file after it is modified", async () => {
+			const filePath = path.join(tmpDir, "cached.txt")
+			await fs.writeFile(filePath, "aaaa")
+			await fs.utimes(filePath, new Date("2024-01-01T00:00:00Z"), new Date("2024-01-01T00:00:00Z"))
+
+			expect(await readFileContentCached(filePath)).toBe("aaaa")
+
+			await fs.writeFile(filePath, "bbbb")
+			await fs.utimes(filePath, new Date("2024-01-02T00:00:00Z"), new Date("2024-01-02T00:00:00Z"))
+
+			expect(await readFileContentCached(filePath)).toBe("bbbb")
+		})
+
+		it("should re-read the file after clearFileCache", async () => {
+			const filePath = path.join(tmpDir, "cached.txt")
+			const mtime = new Date("2024-01-01T00:00:00Z")
+			await fs.writeFile(filePath, "aaaa")
+			await fs.utimes(filePath, mtime, mtime)
+			await readFileContentCached(filePath)
+
+			await fs.writeFile(filePath, "bbbb")
+			await fs.utimes(filePath, mtime, mtime)
+			clearFileCache()
+
+			expect(await readFileContentCached(filePath)).toBe("bbbb")
+		})
+
+		it("should evict the oldest files once the total content exceeds the byte budget", async () => {
+			const mtime = new Date("2024-01-01T00:00:00Z")
+			// Six 6MB files exceed the 32MB budget, which evicts only the first
+			const filePaths = Array.from({ length: 6 }, (_, i) => path.join(tmpDir, `large-${i}.txt`))
+			for (const filePath of filePaths) {
+				await fs.writeFile(filePath, "a".repeat(6 * 1024 * 1024))
+				await fs.utimes(filePath, mtime, mtime)
+				await readFileContentCached(filePath)
+			}
+
+			// Same size and restored mtime: only evicted files are read again
+			for (const filePath of filePaths.slice(0, 2)) {
+				await fs.writeFile(filePath, "b".repeat(6 * 1024 * 1024))
+				await fs.utimes(filePath, mtime, mtime)
+			}
+
+			expect((await readFileContentCached(filePaths[1]))[0]).toBe("a")
+			expect((await readFileContentCached(filePaths[0]))[0]).toBe("b")
+		})
+
+		it("should not cache files above the per-file limit", async () => {
+			const filePath = path.join(tmpDir, "huge.txt")
+			const mtime = new Date("2024-01-01T00:00:00Z")
+			await fs.writeFile(filePath, "a".repeat(9 * 1024 * 1024))
+			await fs.utimes(filePath, mtime, mtime)
+			await readFileContentCached(filePath)
+
+			await fs.writeFile(filePath, "b".repeat(9 * 1024 * 1024))
+			await fs.utimes(filePath, mtime, mtime)
+
+			expect((await readFileContentCached(filePath))[0]).toBe("b")
+		})
+	})
+})
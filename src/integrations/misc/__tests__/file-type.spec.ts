@@ -0,0 +1,164 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import {
+	detectMimeType,
+	detectMimeTypeFromBuffer,
+	getFileTypeCategory,
+	getShebangInterpreter,
+	readMagicBytes,
+} from "../file-type"
+
+const PNG_SIGNATURE = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a])
+
+describe("file-type", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-file-type-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	describe("detectMimeType", () => {
+		it("should sniff a PNG regardless of its extension", async () => {
+			const filePath = path.join(tmpDir, "screenshot.dat")
+			await fs.writeFile(filePath, Buffer.concat([PNG_SIGNATURE, Buffer.alloc(32)]))
+
+			expect(await detectMimeType(filePath)).toBe("image/png")
+		})
+
+		it("should sniff a PDF", async () => {
+			const filePath = path.join(tmpDir, "document")
+			await fs.writeFile(filePath, "%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj\n")
+
+			expect(await detectMimeType(filePath)).toBe("application/pdf")
+		})
+
+		it("should report plain text files as text/plain", async () => {
+			const filePath = path.join(tmpDir, "notes.txt")
+			await fs.writeFile(filePath, "Just some notes\nspanning two lines\n")
+
+			expect(await detectMimeType(filePath)).toBe("text/plain")
+		})
+
+		it("should fall back to the extension when content has no signature", async () => {
+			const filePath = path.join(tmpDir, "config.json")
+			await fs.writeFile(filePath, '{"key": "value"}')
+
+			expect(await detectMimeType(filePath)).toBe("application/json")
+		})
+
+		it("should report unknown binary content as application/octet-stream", async () => {
+			const filePath = path.join(tmpDir, "blob.unknown")
+			await fs.writeFile(filePath, Buffer.from([0x00, 0x13, 0x37, 0x00, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]))
+
+			expect(await detectMimeType(filePath)).toBe("application/octet-stream")
+		})
+	})
+
+	describe("detectMimeTypeFromBuffer", () => {
+		it("should distinguish RIFF containers", () => {
+			const riff = (kind: string) => Buffer.concat([Buffer.from("RIFF"), Buffer.alloc(4), Buffer.from(kind)])
+
+			expect(detectMimeTypeFromBuffer(riff("WEBP"))).toBe("image/webp")
+			expect(detectMimeTypeFromBuffer(riff("WAVE"))).toBe("audio/wav")
+		})
+
+		it("should return undefined for unrecognized content", () => {
+			expect(detectMimeTypeFromBuffer(Buffer.from("hello world"))).toBeUndefined()
+		})
+	})
+
+	describe("getFileTypeCategory", () => {
+		const MP4_HEADER = Buffer.concat([
+			Buffer.from([0x00, 0x00, 0x00, 0x18]),
+			Buffer.from("ftypisom"),
+			Buffer.alloc(12),
+		])
+		const ZIP_HEADER = Buffer.concat([Buffer.from([0x50, 0x4b, 0x03, 0x04]), Buffer.alloc(26)])
+
+		it.each([
+			["photo.png", Buffer.concat([PNG_SIGNATURE, Buffer.alloc(32)]), "image"],
+			["clip.mp4", MP4_HEADER, "video"],
+			["bundle.zip", ZIP_HEADER, "archive"],
+			["report.docx", ZIP_HEADER, "document"],
+			["main.rs", Buffer.from('fn main() {\n    println!("hi");\n}\n'), "code"],
+			["notes.txt", Buffer.from("Just some notes\n"), "text"],
+			["blob.unknown", Buffer.from([0x00, 0x13, 0x37, 0x00, 0xde, 0xad, 0xbe, 0xef, 0x00, 0x01]), "binary"],
+		])("should classify %s", async (name, content, category) => {
+			const filePath = path.join(tmpDir, name)
+			await fs.writeFile(filePath, content)
+
+			expect(await getFileTypeCategory(filePath)).toBe(category)
+		})
+	})
+
+	describe("getShebangInterpreter", () => {
+		it.each([
+			["#!/bin/bash\necho hi\n", "bash"],
+			["#!/usr/bin/env python3\nprint('hi')\n", "python3"],
+			["#!/usr/bin/env node\r\nconsole.log('hi')\r\n", "node"],
+			["#! /bin/sh -e\nset -x\n", "sh"],
+			["#!/usr/bin/env -S deno run --allow-read\n", "deno"],
+			["#!/usr/bin/env NODE_ENV=production node\n", "node"],
+		])("should read the interpreter from %j", async (content, interpreter) => {
+			const filePath = path.join(tmpDir, "script")
+			await fs.writeFile(filePath, content)
+
+			expect(await getShebangInterpreter(filePath)).toBe(interpreter)
+		})
+
+		it("should return null without a shebang", async () => {
+			const filePath = path.join(tmpDir, "plain")
+			await fs.writeFile(filePath, "echo hi\n# !/bin/bash\n")
+
+			expect(await getShebangInterpreter(filePath)).toBeNull()
+		})
+
+		it("should return null for an empty shebang", async () => {
+			const filePath = path.join(tmpDir, "empty-shebang")
+			await fs.writeFile(filePath, "#!\n")
+
+			expect(await getShebangInterpreter(filePath)).toBeNull()
+		})
+	})
+
+	describe("readMagicBytes", () => {
+		it("should read the PNG signature from the first 8 bytes", async () => {
+			const filePath = path.join(tmpDir, "image.png")
+			await fs.writeFile(filePath, Buffer.concat([PNG_SIGNATURE, Buffer.alloc(1024)]))
+
+			const bytes = await readMagicBytes(filePath, 8)
+
+			expect(bytes.equals(PNG_SIGNATURE)).toBe(true)
+			expect(detectMimeTypeFromBuffer(bytes)).toBe("image/png")
+		})
+
+		it("should clamp at the end of the file", async () => {
+			const filePath = path.join(tmpDir, "short.txt")
+			await fs.writeFile(filePath, "abc")
+
+			expect((await readMagicBytes(filePath, 16)).toString()).toBe("abc")
+			expect(await readMagicBytes(filePath, 0)).toHaveLength(0)
+		})
+
+		it("should only allocate as much as the file holds", async () => {
+			const filePath = path.join(tmpDir, "short.txt")
+			await fs.writeFile(filePath, "abc")
+
+			// A buffer of this size can't be allocated, so the read must be sized by the file
+			expect((await readMagicBytes(filePath, Number.MAX_SAFE_INTEGER)).toString()).toBe("abc")
+		})
+
+		it("should reject a negative count", async () => {
+			const filePath = path.join(tmpDir, "short.txt")
+			await fs.writeFile(filePath, "abc")
+
+			await expect(readMagicBytes(filePath, -1)).rejects.toThrow(RangeError)
+		})
+	})
+})
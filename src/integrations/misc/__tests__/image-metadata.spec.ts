@@ -0,0 +1,373 @@
+import {
+	getAspectRatio,
+	getCaptureTimestamp,
+	getColorType,
+	getDimensionsBatch,
+	getFormatDetailed,
+	getFrameDelays,
+	getImageDimensions,
+	getOrientationTag,
+} from "../image-metadata"
+
+function pngHeader(width: number, height: number, bitDepth = 8, colorType = 6): Buffer {
+	const header = Buffer.alloc(33)
+	Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]).copy(header, 0)
+	header.writeUInt32BE(13, 8)
+	header.write("IHDR", 12, "latin1")
+	header.writeUInt32BE(width, 16)
+	header.writeUInt32BE(height, 20)
+	header[24] = bitDepth
+	header[25] = colorType
+	return header
+}
+
+function jpegHeader(width: number, height: number, components = 3, sofMarker = 0xc0): Buffer {
+	const app0 = Buffer.from([0xff, 0xe0, 0x00, 0x10, ...Buffer.from("JFIF\0"), 1, 1, 0, 0, 1, 0, 1, 0, 0])
+	const sof0 = Buffer.alloc(19)
+	sof0.writeUInt16BE(0xff00 | sofMarker, 0)
+	sof0.writeUInt16BE(8 + 3 * components, 2)
+	sof0[4] = 8
+	sof0.writeUInt16BE(height, 5)
+	sof0.writeUInt16BE(width, 7)
+	sof0[9] = components
+	return Buffer.concat([Buffer.from([0xff, 0xd8]), app0, sof0])
+}
+
+function gifHeader(width: number, height: number): Buffer {
+	const header = Buffer.alloc(13)
+	header.write("GIF89a", 0, "latin1")
+	header.writeUInt16LE(width, 6)
+	header.writeUInt16LE(height, 8)
+	return header
+}
+
+function webpVp8xHeader(width: number, height: number): Buffer {
+	const header = Buffer.alloc(30)
+	header.write("RIFF", 0, "latin1")
+	header.write("WEBP", 8, "latin1")
+	header.write("VP8X", 12, "latin1")
+	header.writeUIntLE(width - 1, 24, 3)
+	header.writeUIntLE(height - 1, 27, 3)
+	return header
+}
+
+function bmpHeader(width: number, height: number): Buffer {
+	const header = Buffer.alloc(54)
+	header.write("BM", 0, "latin1")
+	header.writeUInt32LE(40, 14)
+	header.writeInt32LE(width, 18)
+	header.writeInt32LE(height, 22)
+	return header
+}
+
+function animatedGif(delaysCs: Array<number | undefined>): Buffer {
+	const parts = [Buffer.from("GIF89a", "latin1"), Buffer.from([1, 0, 1, 0, 0x80, 0, 0]), Buffer.alloc(6)]
+	for (const delay of delaysCs) {
+		if (delay !== undefined) {
+			parts.push(Buffer.from([0x21, 0xf9, 0x04, 0x00, delay & 0xff, delay >> 8, 0x00, 0x00]))
+		}
+		parts.push(Buffer.from([0x2c, 0, 0, 0, 0, 1, 0, 1, 0, 0x00]), Buffer.from([0x02, 0x02, 0x44, 0x01, 0x00]))
+	}
+	parts.push(Buffer.from([0x3b]))
+	return Buffer.concat(parts)
+}
+
+function pngChunk(type: string, data: Buffer): Buffer {
+	const length = Buffer.alloc(4)
+	length.writeUInt32BE(data.length)
+	return Buffer.concat([length, Buffer.from(type, "latin1"), data, Buffer.alloc(4)])
+}
+
+function fcTL(delayNumerator: number, delayDenominator: number): Buffer {
+	const data = Buffer.alloc(26)
+	data.writeUInt16BE(delayNumerator, 20)
+	data.writeUInt16BE(delayDenominator, 22)
+	return pngChunk("fcTL", data)
+}
+
+function riffChunk(type: string, data: Buffer): Buffer {
+	const size = Buffer.alloc(4)
+	size.writeUInt32LE(data.length)
+	return Buffer.concat([Buffer.from(type, "latin1"), size, data, Buffer.alloc(data.length % 2)])
+}
+
+function webpContainer(chunks: Buffer[]): Buffer {
+	return Buffer.concat([Buffer.from("RIFF", "latin1"), Buffer.alloc(4), Buffer.from("WEBP", "latin1"), ...chunks])
+}
+
+function animatedWebp(durations: number[]): Buffer {
+	const frames = durations.map((duration) => {
+		const header = Buffer.alloc(16)
+		header.writeUIntLE(duration, 12, 3)
+		return riffChunk("ANMF", Buffer.concat([header, Buffer.alloc(3)]))
+	})
+	const vp8x = Buffer.alloc(10)
+	// Animation flag
+	vp8x[0] = 0x02
+	return webpContainer([riffChunk("VP8X", vp8x), riffChunk("ANIM", Buffer.alloc(6)), ...frames])
+}
+
+function jpegWithExif(dateTimeOriginal: string, dateTime: string): Buffer {
+	// Little-endian TIFF: IFD0 with DateTime and an Exif IFD pointer, then the Exif IFD
+	const tiff = Buffer.alloc(8 + 2 + 2 * 12 + 4 + 20 + 2 + 12 + 4 + 20)
+	tiff.write("II", 0, "latin1")
+	tiff.writeUInt16LE(42, 2)
+	tiff.writeUInt32LE(8, 4)
+
+	const dateTimeOffset = 8 + 2 + 2 * 12 + 4
+	const exifIfdOffset = dateTimeOffset + 20
+	const originalOffset = exifIfdOffset + 2 + 12 + 4
+
+	tiff.writeUInt16LE(2, 8)
+	tiff.writeUInt16LE(0x0132, 10)
+	tiff.writeUInt16LE(2, 12)
+	tiff.writeUInt32LE(20, 14)
+	tiff.writeUInt32LE(dateTimeOffset, 18)
+	tiff.writeUInt16LE(0x8769, 22)
+	tiff.writeUInt16LE(4, 24)
+	tiff.writeUInt32LE(1, 26)
+	tiff.writeUInt32LE(exifIfdOffset, 30)
+	tiff.write(dateTime, dateTimeOffset, "latin1")
+
+	tiff.writeUInt16LE(1, exifIfdOffset)
+	tiff.writeUInt16LE(0x9003, exifIfdOffset + 2)
+	tiff.writeUInt16LE(2, exifIfdOffset + 4)
+	tiff.writeUInt32LE(20, exifIfdOffset + 6)
+	tiff.writeUInt32LE(originalOffset, exifIfdOffset + 10)
+	tiff.write(dateTimeOriginal, originalOffset, "latin1")
+
+	return jpegWithTiff(tiff)
+}
+
+function jpegWithTiff(tiff: Buffer): Buffer {
+	const app1 = Buffer.concat([Buffer.from([0xff, 0xe1, 0, 0]), Buffer.from("Exif\0\0", "latin1"), tiff])
+	app1.writeUInt16BE(app1.length - 2, 2)
+	const jpeg = jpegHeader(4, 3)
+	return Buffer.concat([jpeg.subarray(0, 2), app1, jpeg.subarray(2)])
+}
+
+function jpegWithOrientation(orientation: number): Buffer {
+	// Big-endian TIFF with a single SHORT Orientation entry in IFD0
+	const tiff = Buffer.alloc(8 + 2 + 12 + 4)
+	tiff.write("MM", 0, "latin1")
+	tiff.writeUInt16BE(42, 2)
+	tiff.writeUInt32BE(8, 4)
+	tiff.writeUInt16BE(1, 8)
+	tiff.writeUInt16BE(0x0112, 10)
+	tiff.writeUInt16BE(3, 12)
+	tiff.writeUInt32BE(1, 14)
+	tiff.writeUInt16BE(orientation, 18)
+	return jpegWithTiff(tiff)
+}
+
+describe("image-metadata", () => {
+	describe("getImageDimensions", () => {
+		it.each([
+			["PNG", pngHeader(640, 480), "image/png"],
+			["JPEG", jpegHeader(640, 480), "image/jpeg"],
+			["GIF", gifHeader(640, 480), "image/gif"],
+			["WebP", webpVp8xHeader(640, 480), "image/webp"],
+			["BMP", bmpHeader(640, -480), "image/bmp"],
+		])("should read %s dimensions from the header", (_name, data, format) => {
+			expect(getImageDimensions(data)).toEqual({ format, width: 640, height: 480 })
+		})
+
+		it("should return undefined for unsupported or truncated data", () => {
+			expect(getImageDimensions(Buffer.from("not an image"))).toBeUndefined()
+			expect(getImageDimensions(pngHeader(10, 10).subarray(0, 16))).toBeUndefined()
+			expect(getImageDimensions(Buffer.from([0xff, 0xd8, 0xff, 0xd9]))).toBeUndefined()
+		})
+	})
+
+	describe("getDimensionsBatch", () => {
+		it("should return dimensions in input order with null for unreadable entries", () => {
+			const images = [
+				pngHeader(640, 480),
+				Buffer.from("not an image"),
+				jpegHeader(100, 200),
+				Buffer.alloc(0),
+				webpVp8xHeader(32, 16),
+			]
+
+			expect(getDimensionsBatch(images)).toEqual([
+				{ width: 640, height: 480 },
+				null,
+				{ width: 100, height: 200 },
+				null,
+				{ width: 32, height: 16 },
+			])
+		})
+	})
+
+	describe("getAspectRatio", () => {
+		it("should snap 1920x1080 to 16:9", () => {
+			const { ratio, closest } = getAspectRatio(pngHeader(1920, 1080))
+
+			expect(ratio).toBeCloseTo(16 / 9)
+			expect(closest).toBe("16:9")
+		})
+
+		it("should snap portrait and square images", () => {
+			expect(getAspectRatio(jpegHeader(1080, 1920)).closest).toBe("9:16")
+			expect(getAspectRatio(gifHeader(512, 512)).closest).toBe("1:1")
+			expect(getAspectRatio(pngHeader(1366, 768)).closest).toBe("16:9")
+		})
+
+		it("should report other for an uncommon size", () => {
+			expect(getAspectRatio(pngHeader(1000, 123))).toEqual({ ratio: 1000 / 123, closest: "other" })
+		})
+
+		it("should throw when the dimensions can't be read", () => {
+			expect(() => getAspectRatio(Buffer.from("garbage"))).toThrow("Unable to read image dimensions")
+			expect(() => getAspectRatio(pngHeader(0, 100))).toThrow("Unable to read image dimensions")
+		})
+	})
+
+	describe("getFrameDelays", () => {
+		it("should read per-frame delays from an animated GIF", () => {
+			expect(getFrameDelays(animatedGif([10, 20, 50]))).toEqual([100, 200, 500])
+		})
+
+		it("should report 0 for GIF frames without a graphic control extension", () => {
+			expect(getFrameDelays(animatedGif([undefined, 7]))).toEqual([0, 70])
+		})
+
+		it("should read APNG frame delays from fcTL chunks", () => {
+			const apng = Buffer.concat([
+				pngHeader(1, 1),
+				pngChunk("acTL", Buffer.alloc(8)),
+				fcTL(1, 10),
+				pngChunk("IDAT", Buffer.alloc(4)),
+				fcTL(25, 0),
+				pngChunk("fdAT", Buffer.alloc(8)),
+				pngChunk("IEND", Buffer.alloc(0)),
+			])
+
+			expect(getFrameDelays(apng)).toEqual([100, 250])
+		})
+
+		it("should read animated WebP frame durations", () => {
+			expect(getFrameDelays(animatedWebp([40, 80, 120]))).toEqual([40, 80, 120])
+		})
+
+		it("should report a single frame with no delay for static images", () => {
+			const png = Buffer.concat([
+				pngHeader(1, 1),
+				pngChunk("IDAT", Buffer.alloc(4)),
+				pngChunk("IEND", Buffer.alloc(0)),
+			])
+
+			expect(getFrameDelays(png)).toEqual([0])
+			expect(getFrameDelays(jpegHeader(10, 10))).toEqual([0])
+			expect(getFrameDelays(webpVp8xHeader(10, 10))).toEqual([0])
+		})
+
+		it("should throw for unsupported data", () => {
+			expect(() => getFrameDelays(Buffer.from("not an image"))).toThrow("Unsupported image format")
+		})
+	})
+
+	describe("getColorType", () => {
+		it("should report 16-bit grayscale and 8-bit RGBA PNGs", () => {
+			expect(getColorType(pngHeader(1, 1, 16, 0))).toBe("l16")
+			expect(getColorType(pngHeader(1, 1, 8, 6))).toBe("rgba8")
+		})
+
+		it("should map the remaining PNG color types", () => {
+			expect(getColorType(pngHeader(1, 1, 8, 2))).toBe("rgb8")
+			expect(getColorType(pngHeader(1, 1, 16, 6))).toBe("rgba16")
+			expect(getColorType(pngHeader(1, 1, 16, 4))).toBe("la16")
+			expect(getColorType(pngHeader(1, 1, 1, 0))).toBe("l8")
+			expect(getColorType(pngHeader(1, 1, 4, 3))).toBe("rgb8")
+		})
+
+		it("should add alpha when a PNG has a tRNS chunk", () => {
+			const palette = Buffer.concat([
+				pngHeader(1, 1, 8, 3),
+				pngChunk("PLTE", Buffer.alloc(6)),
+				pngChunk("tRNS", Buffer.from([0])),
+				pngChunk("IDAT", Buffer.alloc(4)),
+			])
+
+			expect(getColorType(palette)).toBe("rgba8")
+		})
+
+		it("should report JPEG, GIF and WebP color types", () => {
+			expect(getColorType(jpegHeader(1, 1, 1))).toBe("l8")
+			expect(getColorType(jpegHeader(1, 1, 3))).toBe("rgb8")
+			expect(getColorType(gifHeader(1, 1))).toBe("rgba8")
+			expect(getColorType(webpVp8xHeader(1, 1))).toBe("rgb8")
+		})
+
+		it("should throw for unsupported data", () => {
+			expect(() => getColorType(Buffer.from("not an image"))).toThrow("Unsupported image format")
+		})
+	})
+
+	describe("getCaptureTimestamp", () => {
+		it("should read DateTimeOriginal from a JPEG as UTC", () => {
+			const jpeg = jpegWithExif("2023:07:14 09:30:15", "2024:01:01 00:00:00")
+
+			expect(getCaptureTimestamp(jpeg)).toBe(Date.UTC(2023, 6, 14, 9, 30, 15))
+		})
+
+		it("should fall back to DateTime when DateTimeOriginal is unset", () => {
+			const jpeg = jpegWithExif("0000:00:00 00:00:00", "2024:01:02 03:04:05")
+
+			expect(getCaptureTimestamp(jpeg)).toBe(Date.UTC(2024, 0, 2, 3, 4, 5))
+		})
+
+		it("should return null for images without EXIF", () => {
+			expect(getCaptureTimestamp(jpegHeader(4, 3))).toBeNull()
+			expect(getCaptureTimestamp(pngHeader(4, 3))).toBeNull()
+			expect(getCaptureTimestamp(Buffer.from("not an image"))).toBeNull()
+		})
+	})
+
+	describe("getFormatDetailed", () => {
+		it("should distinguish baseline and progressive JPEGs", () => {
+			expect(getFormatDetailed(jpegHeader(8, 8))).toEqual({ format: "image/jpeg", variant: "baseline" })
+			expect(getFormatDetailed(jpegHeader(8, 8, 3, 0xc2))).toEqual({
+				format: "image/jpeg",
+				variant: "progressive",
+			})
+		})
+
+		it("should report interlaced PNGs", () => {
+			const interlaced = pngHeader(8, 8)
+			interlaced[28] = 1
+
+			expect(getFormatDetailed(interlaced)).toEqual({ format: "image/png", variant: "interlaced" })
+			expect(getFormatDetailed(pngHeader(8, 8))).toEqual({ format: "image/png" })
+		})
+
+		it("should classify WebP encodings", () => {
+			const extendedLossless = webpContainer([
+				riffChunk("VP8X", Buffer.alloc(10)),
+				riffChunk("VP8L", Buffer.alloc(5)),
+			])
+
+			expect(getFormatDetailed(webpContainer([riffChunk("VP8 ", Buffer.alloc(10))])).variant).toBe("lossy")
+			expect(getFormatDetailed(webpContainer([riffChunk("VP8L", Buffer.alloc(5))])).variant).toBe("lossless")
+			expect(getFormatDetailed(animatedWebp([40])).variant).toBe("animated")
+			expect(getFormatDetailed(extendedLossless).variant).toBe("lossless")
+		})
+
+		it("should omit the variant for other formats and throw for unsupported data", () => {
+			expect(getFormatDetailed(gifHeader(1, 1))).toEqual({ format: "image/gif" })
+			expect(() => getFormatDetailed(Buffer.from("not an image"))).toThrow("Unsupported image format")
+		})
+	})
+
+	describe("getOrientationTag", () => {
+		it.each([1, 6, 8])("should read orientation %d", (orientation) => {
+			expect(getOrientationTag(jpegWithOrientation(orientation))).toBe(orientation)
+		})
+
+		it("should default to 1 without EXIF or with an out-of-range value", () => {
+			expect(getOrientationTag(jpegHeader(4, 3))).toBe(1)
+			expect(getOrientationTag(jpegWithExif("2023:07:14 09:30:15", "2023:07:14 09:30:15"))).toBe(1)
+			expect(getOrientationTag(jpegWithOrientation(9))).toBe(1)
+		})
+	})
+})
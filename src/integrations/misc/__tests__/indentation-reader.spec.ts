@@ -5,6 +5,8 @@ import {
 	readWithIndentation,
 	readWithSlice,
 	computeEffectiveIndents,
+	computeIndentationOutline,
+	detectIndentSize,
 	type LineRecord,
 	type IndentationReadResult,
 } from "../indentation-reader"
@@ -637,3 +639,52 @@ describe("readWithIndentation", () => {
 		})
 	})
 })
+
+describe("computeIndentationOutline", () => {
+	const YAML_LIKE = `root:
+  child_a:
+    leaf: 1
+    leaf2: 2
+  child_b:
+    leaf: 3
+other: 4`
+
+	it("should detect the indent size from leading whitespace", () => {
+		expect(detectIndentSize(YAML_LIKE)).toBe(2)
+		expect(detectIndentSize(PYTHON_CODE)).toBe(4)
+		expect(detectIndentSize("a {\n\tb {\n\t\tc\n\t}\n}")).toBe(4)
+	})
+
+	it("should compute depths normalized by the detected indent size", () => {
+		expect(computeIndentationOutline(YAML_LIKE)).toEqual([
+			{ line: 1, depth: 0, text: "root:" },
+			{ line: 2, depth: 1, text: "child_a:" },
+			{ line: 5, depth: 1, text: "child_b:" },
+			{ line: 7, depth: 0, text: "other: 4" },
+		])
+	})
+
+	it("should treat tab indentation as one level per tab", () => {
+		const content = "fn main() {\n\tif x {\n\t\ty()\n\t}\n}"
+		expect(computeIndentationOutline(content)).toEqual([
+			{ line: 1, depth: 0, text: "fn main() {" },
+			{ line: 2, depth: 1, text: "if x {" },
+			{ line: 4, depth: 1, text: "}" },
+			{ line: 5, depth: 0, text: "}" },
+		])
+	})
+
+	it("should include markdown-style headings and skip blank lines", () => {
+		const content = "# Title\n\nSome text\n\n## Section\nMore text"
+		expect(computeIndentationOutline(content)).toEqual([
+			{ line: 1, depth: 0, text: "# Title" },
+			{ line: 5, depth: 0, text: "## Section" },
+		])
+	})
+
+	it("should limit the number of entries", () => {
+		const outline = computeIndentationOutline(PYTHON_CODE, 2)
+		expect(outline).toHaveLength(2)
+		expect(outline[0].text).toBe("class Calculator:")
+	})
+})
@@ -1,5 +1,5 @@
 import { describe, it, expect, vi, beforeEach } from "vitest"
-import { countFileLines, countFileLinesAndTokens } from "../line-counter"
+import { countFileLines, countFileLinesAndTokens, countFileLinesBatch, countLinesInBuffer } from "../line-counter"
 import { countTokens } from "../../../utils/countTokens"
 import { Readable } from "stream"
 
@@ -94,5 +94,80 @@ describe("line-counter", () => {
 
 			await expect(countFileLines("/nonexistent/file.txt")).rejects.toThrow("File not found")
 		})
+
+		it("should count form-feed page separators", async () => {
+			mockFsAccess.mockResolvedValue(undefined)
+			mockCreateReadStream.mockReturnValue(
+				Readable.from([Buffer.from("page 1\fpage 2\n"), Buffer.from("\fpage 3")]) as any,
+			)
+
+			expect(await countFileLines("/test/paged.txt", { separators: ["\f"] })).toBe(3)
+		})
+
+		it("should count any of several separators", async () => {
+			mockFsAccess.mockResolvedValue(undefined)
+			mockCreateReadStream.mockReturnValue(Readable.from([Buffer.from("a\nb\fc\n")]) as any)
+
+			expect(await countFileLines("/test/mixed.txt", { separators: ["\n", "\f"] })).toBe(3)
+		})
+	})
+
+	describe("countLinesInBuffer", () => {
+		it("should default to counting newlines", () => {
+			expect(countLinesInBuffer(Buffer.from("one\ntwo\nthree"))).toBe(3)
+			expect(countLinesInBuffer(Buffer.from("one\ntwo\n"))).toBe(2)
+			expect(countLinesInBuffer(Buffer.alloc(0))).toBe(0)
+		})
+
+		it("should end lines at CRLF and lone CR by default", () => {
+			expect(countLinesInBuffer(Buffer.from("one\rtwo\rthree"))).toBe(3)
+			expect(countLinesInBuffer(Buffer.from("one\rtwo\r"))).toBe(2)
+			expect(countLinesInBuffer(Buffer.from("one\r\ntwo\r\n"))).toBe(2)
+			expect(countLinesInBuffer(Buffer.from("one\r\ntwo\rthree\n"))).toBe(3)
+		})
+
+		it("should count a custom separator", () => {
+			expect(countLinesInBuffer(Buffer.from("a|b|c|"), { separators: ["|"] })).toBe(3)
+			expect(countLinesInBuffer(Buffer.from("a\nb|c"), { separators: ["|"] })).toBe(2)
+		})
+
+		it("should reject separators that are not a single byte", () => {
+			expect(() => countLinesInBuffer(Buffer.from("a"), { separators: ["\r\n"] })).toThrow(RangeError)
+			expect(() => countLinesInBuffer(Buffer.from("a"), { separators: ["é"] })).toThrow(RangeError)
+		})
+	})
+
+	describe("countFileLinesBatch", () => {
+		const files: Record<string, string> = {
+			"/test/a.txt": "one\ntwo\nthree\n",
+			"/test/b.txt": "single line",
+			"/test/c.txt": "",
+		}
+
+		beforeEach(() => {
+			mockFsAccess.mockImplementation(async (filePath) => {
+				if (!(String(filePath) in files)) {
+					throw new Error("ENOENT")
+				}
+			})
+			mockCreateReadStream.mockImplementation((filePath) => Readable.from([files[String(filePath)]]) as any)
+		})
+
+		it("should match serial counts in input order", async () => {
+			const paths = ["/test/c.txt", "/test/a.txt", "/test/b.txt"]
+
+			const batch = await countFileLinesBatch(paths)
+			const serial = []
+			for (const filePath of paths) {
+				serial.push(await countFileLines(filePath))
+			}
+
+			expect(batch).toEqual(serial)
+			expect(batch).toEqual([0, 3, 1])
+		})
+
+		it("should return -1 for files that cannot be read", async () => {
+			expect(await countFileLinesBatch(["/test/a.txt", "/test/missing.txt", "/test/b.txt"])).toEqual([3, -1, 1])
+		})
 	})
 })
@@ -0,0 +1,171 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import { changedLines, deleteLineRange, insertAtLine, replaceLineRange } from "../line-edits"
+
+describe("line-edits", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-line-edits-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	const writeFixture = async (content: string) => {
+		const filePath = path.join(tmpDir, "fixture.txt")
+		await fs.writeFile(filePath, content)
+		return filePath
+	}
+
+	describe("replaceLineRange", () => {
+		it("should replace a single line", async () => {
+			const filePath = await writeFixture("one\ntwo\nthree\n")
+
+			expect(await replaceLineRange(filePath, 2, 2, "TWO")).toBe(3)
+			expect(await fs.readFile(filePath, "utf8")).toBe("one\nTWO\nthree\n")
+		})
+
+		it("should replace a multi-line range with fewer lines", async () => {
+			const filePath = await writeFixture("1\n2\n3\n4\n5\n")
+
+			expect(await replaceLineRange(filePath, 2, 4, "middle")).toBe(3)
+			expect(await fs.readFile(filePath, "utf8")).toBe("1\nmiddle\n5\n")
+		})
+
+		it("should replace with content containing newlines and keep CRLF endings", async () => {
+			const filePath = await writeFixture("a\r\nb\r\nc")
+
+			expect(await replaceLineRange(filePath, 2, 2, "b1\nb2\nb3\n")).toBe(5)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\r\nb1\r\nb2\r\nb3\r\nc")
+		})
+
+		it("should remove the range when the new content is empty", async () => {
+			const filePath = await writeFixture("a\nb\nc\n")
+
+			expect(await replaceLineRange(filePath, 1, 2, "")).toBe(1)
+			expect(await fs.readFile(filePath, "utf8")).toBe("c\n")
+		})
+
+		it("should reject ranges outside the file and leave it untouched", async () => {
+			const filePath = await writeFixture("a\nb\n")
+
+			await expect(replaceLineRange(filePath, 2, 3, "x")).rejects.toThrow(RangeError)
+			await expect(replaceLineRange(filePath, 0, 1, "x")).rejects.toThrow(RangeError)
+			await expect(replaceLineRange(filePath, 2, 1, "x")).rejects.toThrow(RangeError)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\nb\n")
+		})
+	})
+
+	describe("insertAtLine", () => {
+		it("should prepend when inserting at line 1", async () => {
+			const filePath = await writeFixture("b\nc\n")
+
+			expect(await insertAtLine(filePath, 1, "a")).toBe(3)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\nb\nc\n")
+		})
+
+		it("should insert a multi-line block before a middle line", async () => {
+			const filePath = await writeFixture("import a\r\nbody()\r\n")
+
+			expect(await insertAtLine(filePath, 2, "import b\nimport c\n")).toBe(4)
+			expect(await fs.readFile(filePath, "utf8")).toBe("import a\r\nimport b\r\nimport c\r\nbody()\r\n")
+		})
+
+		it("should append when the line is past the end", async () => {
+			const filePath = await writeFixture("a\nb")
+
+			expect(await insertAtLine(filePath, 3, "c")).toBe(3)
+			expect(await insertAtLine(filePath, 100, "d")).toBe(4)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\nb\nc\nd")
+		})
+
+		it("should insert into an empty file", async () => {
+			const filePath = await writeFixture("")
+
+			expect(await insertAtLine(filePath, 1, "first\n")).toBe(1)
+			expect(await fs.readFile(filePath, "utf8")).toBe("first")
+		})
+
+		it("should reject a line below 1", async () => {
+			const filePath = await writeFixture("a\n")
+
+			await expect(insertAtLine(filePath, 0, "x")).rejects.toThrow(RangeError)
+		})
+	})
+
+	describe("deleteLineRange", () => {
+		it("should delete a middle range", async () => {
+			const filePath = await writeFixture("1\n2\n3\n4\n5\n")
+
+			expect(await deleteLineRange(filePath, 2, 4)).toBe(2)
+			expect(await fs.readFile(filePath, "utf8")).toBe("1\n5\n")
+		})
+
+		it("should delete the first line", async () => {
+			const filePath = await writeFixture("header\r\nbody\r\n")
+
+			expect(await deleteLineRange(filePath, 1, 1)).toBe(1)
+			expect(await fs.readFile(filePath, "utf8")).toBe("body\r\n")
+		})
+
+		it("should delete the last line and keep the trailing newline convention", async () => {
+			const withNewline = await writeFixture("a\nb\nc\n")
+			expect(await deleteLineRange(withNewline, 3, 3)).toBe(2)
+			expect(await fs.readFile(withNewline, "utf8")).toBe("a\nb\n")
+
+			const withoutNewline = await writeFixture("a\nb\nc")
+			expect(await deleteLineRange(withoutNewline, 3, 3)).toBe(2)
+			expect(await fs.readFile(withoutNewline, "utf8")).toBe("a\nb")
+		})
+
+		it("should leave an empty file when every line is deleted", async () => {
+			const filePath = await writeFixture("a\nb\n")
+
+			expect(await deleteLineRange(filePath, 1, 2)).toBe(0)
+			expect(await fs.readFile(filePath, "utf8")).toBe("")
+		})
+
+		it("should reject an invalid range", async () => {
+			const filePath = await writeFixture("a\nb\n")
+
+			await expect(deleteLineRange(filePath, 1, 3)).rejects.toThrow(RangeError)
+			await expect(deleteLineRange(filePath, 2, 1)).rejects.toThrow(RangeError)
+		})
+	})
+
+	describe("changedLines", () => {
+		const PREVIOUS = Buffer.from("one\ntwo\nthree\nfour\nfive\n")
+
+		it("should report only a single edited line", async () => {
+			const filePath = await writeFixture("one\ntwo\nTHREE\nfour\nfive\n")
+
+			expect(await changedLines(filePath, PREVIOUS)).toEqual([{ line: 3, type: "modified" }])
+		})
+
+		it("should report added and deleted lines", async () => {
+			const filePath = await writeFixture("one\nthree\nfour\nnew\nfive\nlast\n")
+
+			expect(await changedLines(filePath, PREVIOUS)).toEqual([
+				{ line: 2, type: "deleted" },
+				{ line: 4, type: "added" },
+				{ line: 6, type: "added" },
+			])
+		})
+
+		it("should report lines removed from the end after the last line", async () => {
+			const filePath = await writeFixture("one\ntwo\nthree\n")
+
+			expect(await changedLines(filePath, PREVIOUS)).toEqual([{ line: 4, type: "deleted" }])
+		})
+
+		it("should ignore line ending and trailing newline differences", async () => {
+			const filePath = await writeFixture("one\r\ntwo\r\nthree\r\nfour\r\nfive")
+
+			expect(await changedLines(filePath, PREVIOUS)).toEqual([])
+		})
+	})
+})
@@ -1,6 +1,12 @@
 import { promises as fs } from "fs"
 import path from "path"
-import { readLines } from "../read-lines"
+import {
+	readLines,
+	readLineRangesBatch,
+	readLinesWithLimit,
+	readRange,
+	readLineRangeWithOffsets,
+} from "../read-lines"
 
 describe("nthline", () => {
 	const testFile = path.join(__dirname, "test.txt")
@@ -114,6 +120,22 @@ describe("nthline", () => {
 			})
 		})
 
+		it("should preserve CRLF terminators in the returned range", async () => {
+			await withTempFile("crlf.txt", "Line 1\r\nLine 2\r\nLine 3\r\nLine 4", async (filepath) => {
+				const lines = await readLines(filepath, 2, 1)
+				expect(lines).toEqual("Line 2\r\nLine 3\r\n")
+				expect(await readLines(filepath, 3, 3)).toEqual("Line 4")
+			})
+		})
+
+		it("should keep multi-byte characters intact across stream chunk boundaries", async () => {
+			// Default highWaterMark is 64KB; place a 2-byte character across the boundary
+			const prefix = "a".repeat(64 * 1024 - 1)
+			await withTempFile("chunk-boundary.txt", `${prefix}é\r\nnext\r\n`, async (filepath) => {
+				expect(await readLines(filepath, 0, 0)).toEqual(`${prefix}é\r\n`)
+			})
+		})
+
 		it("should handle files with Unicode characters", async () => {
 			await withTempFile("unicode.txt", "Line 1 😀\nLine 2 你好\nLine 3 こんにちは\n", async (filepath) => {
 				const lines = await readLines(filepath, 1)
@@ -129,4 +151,194 @@ describe("nthline", () => {
 			})
 		})
 	})
+
+	describe("readLineRangesBatch", () => {
+		it("should return results in request order", async () => {
+			const results = await readLineRangesBatch([
+				{ path: testFile, start: 4, end: 5 },
+				{ path: testFile, start: 0, end: 0 },
+			])
+
+			expect(results).toEqual(["Line 5\nLine 6\n", "Line 1\n"])
+		})
+
+		it("should substitute error markers for failing requests without aborting the batch", async () => {
+			const results = await readLineRangesBatch([
+				{ path: testFile, start: 1, end: 1 },
+				{ path: path.join(__dirname, "does-not-exist.txt"), start: 0, end: 1 },
+				{ path: testFile, start: 20, end: 25 },
+				{ path: testFile, start: 3, end: 1 },
+				{ path: testFile, start: 9, end: 9 },
+			])
+
+			expect(results).toHaveLength(5)
+			expect(results[0]).toBe("Line 2\n")
+			expect(results[1]).toEqual({ error: expect.stringContaining("ENOENT") })
+			expect(results[2]).toEqual({ error: expect.stringContaining("does not exist") })
+			expect(results[3]).toEqual({ error: "startLine (3) must be less than or equal to endLine (1)" })
+			expect(results[4]).toBe("Line 10")
+		})
+
+		it("should return an empty array for an empty batch", async () => {
+			expect(await readLineRangesBatch([])).toEqual([])
+		})
+	})
+
+	describe("readLinesWithLimit", () => {
+		async function withTempFile(filename: string, content: string, testFn: (filepath: string) => Promise<void>) {
+			const filepath = path.join(__dirname, filename)
+			await fs.writeFile(filepath, content)
+			try {
+				await testFn(filepath)
+			} finally {
+				await fs.unlink(filepath)
+			}
+		}
+
+		it("should stop at the line limit and flag truncation", async () => {
+			const result = await readLinesWithLimit(testFile, 3)
+
+			expect(result).toEqual({ content: "Line 1\nLine 2\nLine 3\n", lineCount: 3, truncated: true })
+		})
+
+		it("should not flag truncation when the file fits within the limit", async () => {
+			expect(await readLinesWithLimit(testFile, 10)).toEqual({
+				content: Array.from({ length: 10 }, (_, i) => `Line ${i + 1}`).join("\n"),
+				lineCount: 10,
+				truncated: false,
+			})
+		})
+
+		it("should flag truncation when the limit ends exactly at a newline with more content after", async () => {
+			await withTempFile("exact-limit.txt", "a\nb\n", async (filepath) => {
+				expect(await readLinesWithLimit(filepath, 1)).toEqual({ content: "a\n", lineCount: 1, truncated: true })
+				expect(await readLinesWithLimit(filepath, 2)).toEqual({
+					content: "a\nb\n",
+					lineCount: 2,
+					truncated: false,
+				})
+			})
+		})
+
+		it("should handle many short lines spanning multiple stream chunks", async () => {
+			const lines = Array.from({ length: 100_000 }, (_, i) => `${i}`)
+			await withTempFile("many-lines.txt", lines.join("\n"), async (filepath) => {
+				const result = await readLinesWithLimit(filepath, 50_000)
+
+				expect(result.truncated).toBe(true)
+				expect(result.lineCount).toBe(50_000)
+				expect(result.content).toBe(lines.slice(0, 50_000).join("\n") + "\n")
+			})
+		})
+
+		it("should preserve CRLF terminators", async () => {
+			await withTempFile("crlf-limit.txt", "a\r\nb\r\nc\r\n", async (filepath) => {
+				expect((await readLinesWithLimit(filepath, 2)).content).toBe("a\r\nb\r\n")
+			})
+		})
+
+		it("should reject an invalid limit", async () => {
+			await expect(readLinesWithLimit(testFile, -1)).rejects.toThrow(RangeError)
+		})
+	})
+
+	describe("readRange", () => {
+		const SOURCE = "const héllo = 1\r\nlet wörld = 😀 + 2\nreturn done\n"
+
+		async function withSourceFile(testFn: (filepath: string) => Promise<void>) {
+			const filepath = path.join(__dirname, "range.txt")
+			await fs.writeFile(filepath, SOURCE)
+			try {
+				await testFn(filepath)
+			} finally {
+				await fs.unlink(filepath)
+			}
+		}
+
+		it("should extract a partial selection within one line", async () => {
+			expect(await readRange(testFile, 2, 2, 2, 6)).toBe("ne 3")
+		})
+
+		it("should count multibyte characters as single columns", async () => {
+			await withSourceFile(async (filepath) => {
+				expect(await readRange(filepath, 0, 6, 0, 11)).toBe("héllo")
+				expect(await readRange(filepath, 1, 4, 1, 13)).toBe("wörld = 😀")
+			})
+		})
+
+		it("should extract a multi-line selection with partial first and last lines", async () => {
+			await withSourceFile(async (filepath) => {
+				expect(await readRange(filepath, 0, 12, 2, 6)).toBe("= 1\r\nlet wörld = 😀 + 2\nreturn")
+			})
+		})
+
+		it("should clamp columns and lines past the end", async () => {
+			await withSourceFile(async (filepath) => {
+				expect(await readRange(filepath, 0, 100, 1, 3)).toBe("\r\nlet")
+				expect(await readRange(filepath, 2, 7, 50, 0)).toBe("done\n")
+			})
+		})
+
+		it("should return an empty string for an empty selection", async () => {
+			expect(await readRange(testFile, 1, 3, 1, 3)).toBe("")
+		})
+
+		it("should reject an end before the start and negative positions", async () => {
+			await expect(readRange(testFile, 2, 0, 1, 0)).rejects.toThrow(RangeError)
+			await expect(readRange(testFile, 1, 5, 1, 2)).rejects.toThrow(RangeError)
+			await expect(readRange(testFile, -1, 0, 1, 0)).rejects.toThrow(RangeError)
+		})
+
+		it("should reject a start line past the end of the file", async () => {
+			await expect(readRange(testFile, 20, 0, 21, 0)).rejects.toThrow("does not exist")
+		})
+	})
+
+	describe("readLineRangeWithOffsets", () => {
+		it("should return byte offsets that follow the preceding newlines", async () => {
+			const filepath = path.join(__dirname, "offsets.txt")
+			await fs.writeFile(filepath, "héllo\r\n😀 wörld\nthird\nlast")
+			try {
+				const data = await fs.readFile(filepath)
+				const { content, lineOffsets } = await readLineRangeWithOffsets(filepath, 1, 3)
+
+				expect(content).toBe(await readLines(filepath, 3, 1))
+				expect(lineOffsets).toEqual([8, 20, 26])
+				for (const offset of lineOffsets) {
+					expect(data[offset - 1]).toBe(0x0a)
+				}
+			} finally {
+				await fs.unlink(filepath)
+			}
+		})
+
+		it("should start at offset 0 and stop at the end of the file", async () => {
+			const { content, lineOffsets } = await readLineRangeWithOffsets(testFile, 0, 50)
+
+			expect(content).toBe(await readLines(testFile))
+			expect(lineOffsets).toHaveLength(10)
+			expect(lineOffsets[0]).toBe(0)
+			expect(lineOffsets[1]).toBe("Line 1\n".length)
+		})
+
+		it("should track offsets across stream chunks", async () => {
+			const filepath = path.join(__dirname, "large-offsets.txt")
+			const lines = Array.from({ length: 5000 }, (_, i) => `line ${i} ${"x".repeat(i % 50)}\n`)
+			await fs.writeFile(filepath, lines.join(""))
+			try {
+				const { content, lineOffsets } = await readLineRangeWithOffsets(filepath, 2000, 4500)
+
+				expect(content).toBe(lines.slice(2000, 4501).join(""))
+				expect(lineOffsets[0]).toBe(lines.slice(0, 2000).join("").length)
+				expect(lineOffsets[2500]).toBe(lines.slice(0, 4500).join("").length)
+			} finally {
+				await fs.unlink(filepath)
+			}
+		})
+
+		it("should reject invalid ranges and a start past the end of the file", async () => {
+			await expect(readLineRangeWithOffsets(testFile, 3, 1)).rejects.toThrow(RangeError)
+			await expect(readLineRangeWithOffsets(testFile, 20, 25)).rejects.toThrow("does not exist")
+		})
+	})
 })
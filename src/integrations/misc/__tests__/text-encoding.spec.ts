@@ -0,0 +1,111 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import { decodeUtf16, detectBom, readUtf16File, validateUtf8 } from "../text-encoding"
+
+const TEXT = "PS> Get-Date\r\nTranscript ✓ 😀\r\n"
+
+const encodeUtf16be = (text: string) => Buffer.from(text, "utf16le").swap16()
+
+describe("text-encoding", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-text-encoding-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	describe("readUtf16File", () => {
+		it("should read UTF-16LE with a BOM, including surrogate pairs", async () => {
+			const filePath = path.join(tmpDir, "transcript-le.txt")
+			await fs.writeFile(filePath, Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from(TEXT, "utf16le")]))
+
+			expect(await readUtf16File(filePath)).toBe(TEXT)
+		})
+
+		it("should read UTF-16BE with a BOM, including surrogate pairs", async () => {
+			const filePath = path.join(tmpDir, "transcript-be.txt")
+			await fs.writeFile(filePath, Buffer.concat([Buffer.from([0xfe, 0xff]), encodeUtf16be(TEXT)]))
+
+			expect(await readUtf16File(filePath)).toBe(TEXT)
+		})
+
+		it("should default to little-endian when there is no BOM", async () => {
+			const filePath = path.join(tmpDir, "no-bom.txt")
+			await fs.writeFile(filePath, Buffer.from(TEXT, "utf16le"))
+
+			expect(await readUtf16File(filePath)).toBe(TEXT)
+		})
+	})
+
+	describe("decodeUtf16", () => {
+		it("should ignore a trailing odd byte", () => {
+			const data = Buffer.concat([Buffer.from([0xff, 0xfe]), Buffer.from("ok", "utf16le"), Buffer.from([0x41])])
+
+			expect(decodeUtf16(data)).toBe("ok")
+		})
+
+		it("should return an empty string for empty input or a lone BOM", () => {
+			expect(decodeUtf16(Buffer.alloc(0))).toBe("")
+			expect(decodeUtf16(Buffer.from([0xff, 0xfe]))).toBe("")
+		})
+	})
+
+	describe("validateUtf8", () => {
+		it("should accept valid UTF-8 including multibyte characters", async () => {
+			const filePath = path.join(tmpDir, "valid.txt")
+			await fs.writeFile(filePath, "héllo ✓ 😀\n")
+
+			expect(await validateUtf8(filePath)).toEqual({ valid: true })
+		})
+
+		it("should report the offset and value of an invalid byte", async () => {
+			const filePath = path.join(tmpDir, "latin1.txt")
+			// "café" written as Latin-1 after 4 bytes of valid multibyte UTF-8
+			await fs.writeFile(filePath, Buffer.concat([Buffer.from("✓ ca"), Buffer.from([0x66, 0xe9, 0x0a])]))
+
+			expect(await validateUtf8(filePath)).toEqual({ valid: false, firstInvalidByteOffset: 7, byteValue: 0xe9 })
+		})
+
+		it.each([
+			["an overlong encoding", [0x41, 0xc0, 0xaf], 1],
+			["an encoded surrogate", [0xed, 0xa0, 0x80], 0],
+			["a stray continuation byte", [0x41, 0x42, 0x80], 2],
+			["a sequence truncated at end of file", [0x41, 0xf0, 0x9f, 0x98], 1],
+		])("should reject %s", async (_name, bytes, offset) => {
+			const filePath = path.join(tmpDir, "invalid.bin")
+			await fs.writeFile(filePath, Buffer.from(bytes))
+
+			expect(await validateUtf8(filePath)).toMatchObject({ valid: false, firstInvalidByteOffset: offset })
+		})
+	})
+
+	describe("detectBom", () => {
+		it.each([
+			["utf-8", [0xef, 0xbb, 0xbf]],
+			["utf-16le", [0xff, 0xfe]],
+			["utf-16be", [0xfe, 0xff]],
+			["utf-32le", [0xff, 0xfe, 0x00, 0x00]],
+			["utf-32be", [0x00, 0x00, 0xfe, 0xff]],
+		])("should detect a %s BOM", async (encoding, bom) => {
+			const filePath = path.join(tmpDir, `${encoding}.txt`)
+			await fs.writeFile(filePath, Buffer.concat([Buffer.from(bom), Buffer.from("A\0", "latin1")]))
+
+			expect(await detectBom(filePath)).toBe(encoding)
+		})
+
+		it("should return null without a BOM or when the file is too short", async () => {
+			const plain = path.join(tmpDir, "plain.txt")
+			const short = path.join(tmpDir, "short.txt")
+			await fs.writeFile(plain, "hello")
+			await fs.writeFile(short, Buffer.from([0xef]))
+
+			expect(await detectBom(plain)).toBeNull()
+			expect(await detectBom(short)).toBeNull()
+		})
+	})
+})
@@ -0,0 +1,316 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import {
+	compilePattern,
+	countLinesMatching,
+	countLiteral,
+	extractCaptures,
+	findMatchesByLine,
+	findSpans,
+	scanSpans,
+} from "../text-search"
+
+describe("text-search", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-text-search-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	const writeFixture = async (name: string, content: string) => {
+		const filePath = path.join(tmpDir, name)
+		await fs.writeFile(filePath, content)
+		return filePath
+	}
+
+	describe("compilePattern", () => {
+		it("should always be Unicode-aware and only add flags that are asked for", () => {
+			expect(compilePattern("a").flags).toBe("gu")
+			expect(compilePattern("a", { caseInsensitive: true }).flags).toBe("giu")
+			expect(compilePattern("a", { caseInsensitive: true }, false).flags).toBe("iu")
+			expect(compilePattern("\\p{L}+", {}, false).test("é")).toBe(true)
+		})
+	})
+
+	describe("countLiteral", () => {
+		it("should count every occurrence across lines", async () => {
+			const filePath = await writeFixture("log.txt", "TODO one\nnothing\nTODO two TODO three\n")
+
+			expect(await countLiteral(filePath, "TODO")).toBe(3)
+		})
+
+		it("should count matches that span a line boundary", async () => {
+			const filePath = await writeFixture("split.txt", "end\nstart\nend\nstart")
+
+			expect(await countLiteral(filePath, "end\nstart")).toBe(2)
+		})
+
+		it("should count non-overlapping occurrences", async () => {
+			const filePath = await writeFixture("repeat.txt", "aaaaa")
+
+			expect(await countLiteral(filePath, "aa")).toBe(2)
+		})
+
+		it("should handle multibyte needles", async () => {
+			const filePath = await writeFixture("unicode.txt", "héllo wörld héllo")
+
+			expect(await countLiteral(filePath, "héllo")).toBe(2)
+		})
+
+		it("should return 0 for an empty needle or no matches", async () => {
+			const filePath = await writeFixture("plain.txt", "some content")
+
+			expect(await countLiteral(filePath, "")).toBe(0)
+			expect(await countLiteral(filePath, "missing")).toBe(0)
+		})
+	})
+
+	describe("findSpans", () => {
+		it("should return one span per occurrence without content", async () => {
+			const filePath = await writeFixture("spans.txt", "foo bar foo\nnone\nbarfoo\n")
+
+			expect(await findSpans(filePath, "foo")).toEqual([
+				{ line: 1, startCol: 0, endCol: 3 },
+				{ line: 1, startCol: 8, endCol: 11 },
+				{ line: 3, startCol: 3, endCol: 6 },
+			])
+		})
+
+		it("should use character columns rather than byte offsets for multibyte text", async () => {
+			const filePath = await writeFixture("unicode.txt", "héllo wörld 😀 wörld\n")
+
+			const spans = await findSpans(filePath, "wörld")
+
+			// Byte offsets would be 7 and 19; UTF-16 offsets would put the second match at 15
+			expect(spans).toEqual([
+				{ line: 1, startCol: 6, endCol: 11 },
+				{ line: 1, startCol: 14, endCol: 19 },
+			])
+		})
+
+		it("should support case-insensitive matching", async () => {
+			const filePath = await writeFixture("case.txt", "Error error ERROR\r\n")
+
+			expect(await findSpans(filePath, "error")).toHaveLength(1)
+			expect(await findSpans(filePath, "error", { caseInsensitive: true })).toHaveLength(3)
+		})
+
+		it("should skip empty matches", async () => {
+			const filePath = await writeFixture("empty-match.txt", "abc\n")
+
+			expect(await findSpans(filePath, "x*")).toEqual([])
+		})
+
+		it("should collapse occurrences to the first one per line with dedupLines", async () => {
+			const filePath = await writeFixture("dedup.txt", "foo bar foo\nnone\nfoofoo\n")
+
+			expect(await findSpans(filePath, "foo", { dedupLines: true })).toEqual([
+				{ line: 1, startCol: 0, endCol: 3 },
+				{ line: 3, startCol: 0, endCol: 3 },
+			])
+		})
+
+		it("should keep same-line occurrences in column order when sorting by line", async () => {
+			const filePath = await writeFixture("sorted.txt", "a1 a2\nb\na3 a4 a5\n")
+
+			const sorted = await findSpans(filePath, "a\\d", { sortBy: "line" })
+
+			expect(sorted).toEqual(await findSpans(filePath, "a\\d", { sortBy: "none" }))
+			expect(sorted.map((span) => [span.line, span.startCol])).toEqual([
+				[1, 0],
+				[1, 3],
+				[3, 0],
+				[3, 3],
+				[3, 6],
+			])
+		})
+
+		it("should include file-absolute byte offsets that differ from character columns", async () => {
+			const content = "héllo\r\n😀 wörld wörld\n"
+			const filePath = await writeFixture("bytes.txt", content)
+			const data = Buffer.from(content)
+
+			const spans = await findSpans(filePath, "wörld", { byteOffsets: true })
+
+			expect(spans).toEqual([
+				{ line: 2, startCol: 2, endCol: 7, byteStart: 13, byteEnd: 19 },
+				{ line: 2, startCol: 8, endCol: 13, byteStart: 20, byteEnd: 26 },
+			])
+			for (const span of spans) {
+				expect(data.subarray(span.byteStart, span.byteEnd).toString()).toBe("wörld")
+			}
+			expect(await findSpans(filePath, "wörld")).toEqual([
+				{ line: 2, startCol: 2, endCol: 7 },
+				{ line: 2, startCol: 8, endCol: 13 },
+			])
+		})
+
+		it("should stay linear on a long single line with many matches", async () => {
+			// 100k matches on one line; re-measuring each match's prefix would take minutes
+			const filePath = await writeFixture("minified.js", "😀b".repeat(100_000))
+
+			const spans = await findSpans(filePath, "b")
+
+			expect(spans).toHaveLength(100_000)
+			expect(spans[spans.length - 1]).toEqual({ line: 1, startCol: 199_999, endCol: 200_000 })
+
+			const [line] = await findMatchesByLine(filePath, "b")
+			expect(line.matches).toHaveLength(100_000)
+		})
+
+		it("should stay linear with byte offsets on a long single line", async () => {
+			const filePath = await writeFixture("minified.js", "x\n" + "😀b".repeat(100_000))
+
+			const spans = await findSpans(filePath, "b", { byteOffsets: true })
+
+			expect(spans).toHaveLength(100_000)
+			// Each "😀b" is 5 bytes, after the 2-byte first line
+			expect(spans[spans.length - 1]).toEqual({
+				line: 2,
+				startCol: 199_999,
+				endCol: 200_000,
+				byteStart: 2 + 500_000 - 1,
+				byteEnd: 2 + 500_000,
+			})
+		})
+
+		describe("with timeoutMs", () => {
+			it("should return every span when the search finishes in time", async () => {
+				const filePath = await writeFixture("spans.txt", "foo bar foo\nnone\nbarfoo\n")
+
+				expect(await findSpans(filePath, "foo", { timeoutMs: 10_000, byteOffsets: true })).toEqual({
+					spans: await findSpans(filePath, "foo", { byteOffsets: true }),
+					timedOut: false,
+				})
+			})
+
+			it("should stop at the deadline and return the spans found so far", async () => {
+				// Lines without the final "b" make (a+)+b backtrack exponentially
+				const lines = Array.from({ length: 400 }, (_, i) => "a".repeat(22) + (i % 2 === 0 ? "b" : ""))
+				const filePath = await writeFixture("large.txt", lines.join("\n"))
+
+				const result = await findSpans(filePath, "(a+)+b", { timeoutMs: 300 })
+
+				expect(result.timedOut).toBe(true)
+				expect(result.spans.length).toBeGreaterThan(0)
+				expect(result.spans.length).toBeLessThan(200)
+				expect(result.spans[0]).toEqual({ line: 1, startCol: 0, endCol: 23 })
+			})
+
+			it("should hand spans over in batches so a terminated worker keeps its progress", () => {
+				const content = Array.from({ length: 2500 }, () => "foo").join("\n")
+				const batches: number[] = []
+
+				const rest = scanSpans(content, "foo", "gu", false, Infinity, (spans) => batches.push(spans.length))
+
+				expect(batches).toEqual([1000, 1000])
+				expect(rest.spans).toHaveLength(500)
+				expect(rest.spans[0]).toEqual({ line: 2001, startCol: 0, endCol: 3 })
+			})
+
+			it("should reject a timeout that is not a positive number", async () => {
+				const filePath = await writeFixture("spans.txt", "foo\n")
+
+				await expect(findSpans(filePath, "foo", { timeoutMs: 0 })).rejects.toThrow(RangeError)
+				await expect(findSpans(filePath, "foo", { timeoutMs: NaN })).rejects.toThrow(RangeError)
+			})
+		})
+	})
+
+	describe("findMatchesByLine", () => {
+		it("should nest every occurrence on a line under a single entry", async () => {
+			const filePath = await writeFixture("grouped.txt", "foo bar foo\nnone\nbarfoo\n")
+
+			expect(await findMatchesByLine(filePath, "foo")).toEqual([
+				{
+					line: 1,
+					content: "foo bar foo",
+					matches: [
+						{ startCol: 0, endCol: 3 },
+						{ startCol: 8, endCol: 11 },
+					],
+				},
+				{ line: 3, content: "barfoo", matches: [{ startCol: 3, endCol: 6 }] },
+			])
+		})
+
+		it("should report the same occurrences as findSpans", async () => {
+			const filePath = await writeFixture("same.txt", "Error error\nok\nERROR\r\n")
+
+			const grouped = await findMatchesByLine(filePath, "error", { caseInsensitive: true })
+			const flattened = grouped.flatMap(({ line, matches }) => matches.map((match) => ({ line, ...match })))
+
+			expect(flattened).toEqual(await findSpans(filePath, "error", { caseInsensitive: true }))
+			expect(grouped.map((entry) => entry.content)).toEqual(["Error error", "ERROR"])
+		})
+	})
+
+	describe("countLinesMatching", () => {
+		it("should count matching lines rather than occurrences", async () => {
+			const filePath = await writeFixture("app.log", "ERROR one ERROR two\ninfo\nERROR three\nwarn\n")
+
+			expect(await countLinesMatching(filePath, "ERROR")).toBe(2)
+		})
+
+		it("should support case-insensitive matching", async () => {
+			const filePath = await writeFixture("case.log", "Error\r\nerror\r\nERROR\r\nok\r\n")
+
+			expect(await countLinesMatching(filePath, "error")).toBe(1)
+			expect(await countLinesMatching(filePath, "error", { caseInsensitive: true })).toBe(3)
+		})
+
+		it("should return 0 when nothing matches", async () => {
+			const filePath = await writeFixture("none.log", "all good\n")
+
+			expect(await countLinesMatching(filePath, "ERROR")).toBe(0)
+		})
+	})
+
+	describe("extractCaptures", () => {
+		const LOG = [
+			"2024-05-01T10:00:00Z INFO request id=abc123 ok",
+			"continuation line without a timestamp",
+			"2024-05-01T10:00:05Z ERROR request id=def456 failed",
+			"",
+		].join("\n")
+
+		it("should extract the full match and numbered groups from matching lines", async () => {
+			const filePath = await writeFixture("app.log", LOG)
+
+			expect(await extractCaptures(filePath, "^(\\S+) \\w+ request id=(\\w+)")).toEqual([
+				{ line: 1, groups: ["2024-05-01T10:00:00Z INFO request id=abc123", "2024-05-01T10:00:00Z", "abc123"] },
+				{ line: 3, groups: ["2024-05-01T10:00:05Z ERROR request id=def456", "2024-05-01T10:00:05Z", "def456"] },
+			])
+		})
+
+		it("should key named groups, including the (?P<name>) form", async () => {
+			const filePath = await writeFixture("app.log", LOG)
+
+			const captures = await extractCaptures(filePath, "^(?P<time>\\d\\S+) (?<level>[A-Z]+)")
+
+			expect(captures.map((capture) => capture.named)).toEqual([
+				{ time: "2024-05-01T10:00:00Z", level: "INFO" },
+				{ time: "2024-05-01T10:00:05Z", level: "ERROR" },
+			])
+			expect(captures[1].groups).toEqual(["2024-05-01T10:00:05Z ERROR", "2024-05-01T10:00:05Z", "ERROR"])
+		})
+
+		it("should report groups that did not participate as empty strings", async () => {
+			const filePath = await writeFixture("optional.txt", "id=1\nid=2 tag=x\n")
+
+			const captures = await extractCaptures(filePath, "id=(\\d+)(?: tag=(\\w+))?")
+
+			expect(captures.map((capture) => capture.groups)).toEqual([
+				["id=1", "1", ""],
+				["id=2 tag=x", "2", "x"],
+			])
+			expect(captures[0].named).toBeUndefined()
+		})
+	})
+})
@@ -0,0 +1,579 @@
+const { writtenChunkSizes } = vi.hoisted(() => ({ writtenChunkSizes: [] as number[] }))
+
+// Pass through to the real implementation while recording the size of every streamed chunk
+vi.mock("../../../utils/fs", async (importOriginal) => {
+	const actual = await importOriginal<typeof import("../../../utils/fs")>()
+	return {
+		...actual,
+		writeFileAtomicStream: (
+			filePath: string,
+			chunks: AsyncIterable<string | Buffer>,
+			shouldCommit?: () => boolean,
+		) => {
+			async function* recorded() {
+				for await (const chunk of chunks) {
+					writtenChunkSizes.push(chunk.length)
+					yield chunk
+				}
+			}
+			return actual.writeFileAtomicStream(filePath, recorded(), shouldCommit)
+		},
+	}
+})
+
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import {
+	stripAnsi,
+	stripAnsiFromBuffer,
+	stripAnsiInPlace,
+	convertTabsToSpaces,
+	convertSpacesToTabs,
+	formatJson,
+	expandTabs,
+	readFileForDisplay,
+	hasTrailingNewline,
+	ensureTrailingNewline,
+	replaceInFileStreaming,
+	normalizeWhitespace,
+	previewReplaceInFiles,
+	applyBasicFormatting,
+	wrapLines,
+} from "../text-transforms"
+
+describe("text-transforms", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-text-transforms-"))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	describe("stripAnsi", () => {
+		const COLORED_LOG = "\x1b[32m✓\x1b[0m passed\n\x1b[1;31mError:\x1b[0m failed\n\x1b[2K\x1b[1Gdone\n"
+		const CLEAN_LOG = "✓ passed\nError: failed\ndone\n"
+
+		it("should remove color and cursor sequences while keeping visible text", async () => {
+			const filePath = path.join(tmpDir, "terminal.log")
+			await fs.writeFile(filePath, COLORED_LOG)
+
+			const result = await stripAnsi(filePath)
+
+			expect(result).toBe(CLEAN_LOG)
+			expect(result).not.toContain("\x1b")
+		})
+
+		it("should strip sequences from a buffer", () => {
+			expect(stripAnsiFromBuffer(Buffer.from(COLORED_LOG))).toBe(CLEAN_LOG)
+		})
+
+		it("should rewrite the file in place and report the bytes removed", async () => {
+			const filePath = path.join(tmpDir, "terminal.log")
+			await fs.writeFile(filePath, COLORED_LOG)
+
+			const removed = await stripAnsiInPlace(filePath)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe(CLEAN_LOG)
+			expect(removed).toBe(Buffer.byteLength(COLORED_LOG) - Buffer.byteLength(CLEAN_LOG))
+		})
+
+		it("should return 0 when the file has no escape sequences", async () => {
+			const filePath = path.join(tmpDir, "plain.log")
+			await fs.writeFile(filePath, CLEAN_LOG)
+
+			expect(await stripAnsiInPlace(filePath)).toBe(0)
+			expect(await fs.readFile(filePath, "utf8")).toBe(CLEAN_LOG)
+		})
+	})
+
+	describe("convertTabsToSpaces", () => {
+		it("should expand leading tabs only and report changed lines", async () => {
+			const filePath = path.join(tmpDir, "tabs.go")
+			await fs.writeFile(filePath, 'func main() {\n\tx := "a\\tb"\n\t\tif x {\ty()\n}\n')
+
+			const changed = await convertTabsToSpaces(filePath, 4)
+
+			expect(changed).toBe(2)
+			expect(await fs.readFile(filePath, "utf8")).toBe(
+				'func main() {\n    x := "a\\tb"\n        if x {\ty()\n}\n',
+			)
+		})
+
+		it("should honor tab stops when spaces precede a tab", async () => {
+			const filePath = path.join(tmpDir, "mixed.txt")
+			await fs.writeFile(filePath, "  \tvalue\n")
+
+			await convertTabsToSpaces(filePath, 4)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe("    value\n")
+		})
+
+		it("should preserve CRLF line endings", async () => {
+			const filePath = path.join(tmpDir, "crlf.txt")
+			await fs.writeFile(filePath, "a\r\n\tb\r\n")
+
+			expect(await convertTabsToSpaces(filePath, 2)).toBe(1)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\r\n  b\r\n")
+		})
+
+		it("should reject a non-positive tab width", async () => {
+			await expect(convertTabsToSpaces(path.join(tmpDir, "missing.txt"), 0)).rejects.toThrow(RangeError)
+		})
+	})
+
+	describe("convertSpacesToTabs", () => {
+		it("should collapse leading spaces into tabs and leave inner spaces alone", async () => {
+			const filePath = path.join(tmpDir, "spaces.py")
+			await fs.writeFile(filePath, "def f():\n    x = 1    # comment\n        return x\n")
+
+			const changed = await convertSpacesToTabs(filePath, 4)
+
+			expect(changed).toBe(2)
+			expect(await fs.readFile(filePath, "utf8")).toBe("def f():\n\tx = 1    # comment\n\t\treturn x\n")
+		})
+
+		it("should keep a remainder that does not fill a tab as spaces", async () => {
+			const filePath = path.join(tmpDir, "remainder.txt")
+			await fs.writeFile(filePath, "      value\n")
+
+			await convertSpacesToTabs(filePath, 4)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe("\t  value\n")
+		})
+
+		it("should not rewrite a file that is already tab-indented", async () => {
+			const filePath = path.join(tmpDir, "tabs.txt")
+			await fs.writeFile(filePath, "\tvalue\n")
+
+			expect(await convertSpacesToTabs(filePath, 4)).toBe(0)
+		})
+	})
+
+	describe("formatJson", () => {
+		it("should pretty-print minified JSON and return the bytes written", async () => {
+			const filePath = path.join(tmpDir, "config.json")
+			await fs.writeFile(filePath, '{"name":"roo","tags":["a","b"],"nested":{"enabled":true}}')
+
+			const bytesWritten = await formatJson(filePath)
+
+			const expected = [
+				"{",
+				'  "name": "roo",',
+				'  "tags": [',
+				'    "a",',
+				'    "b"',
+				"  ],",
+				'  "nested": {',
+				'    "enabled": true',
+				"  }",
+				"}",
+				"",
+			].join("\n")
+			expect(await fs.readFile(filePath, "utf8")).toBe(expected)
+			expect(bytesWritten).toBe(Buffer.byteLength(expected))
+		})
+
+		it("should honor a custom indent", async () => {
+			const filePath = path.join(tmpDir, "config.json")
+			await fs.writeFile(filePath, '{"a":[1]}')
+
+			await formatJson(filePath, 4)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe('{\n    "a": [\n        1\n    ]\n}\n')
+		})
+
+		it("should throw EJSON with the error location and leave invalid JSON untouched", async () => {
+			const filePath = path.join(tmpDir, "broken.json")
+			const original = '{\n  "a": 1,\n}'
+			await fs.writeFile(filePath, original)
+
+			await expect(formatJson(filePath)).rejects.toMatchObject({ code: "EJSON", line: 3, column: 1 })
+			expect(await fs.readFile(filePath, "utf8")).toBe(original)
+		})
+
+		it("should reject an invalid indent", async () => {
+			await expect(formatJson(path.join(tmpDir, "config.json"), -1)).rejects.toThrow(RangeError)
+		})
+
+		it("should refuse to rewrite integers beyond double precision", async () => {
+			const filePath = path.join(tmpDir, "ids.json")
+			const original = '{"id": 12345678901234567890}'
+			await fs.writeFile(filePath, original)
+
+			await expect(formatJson(filePath)).rejects.toMatchObject({ code: "EJSON_LOSSY", line: 1, column: 8 })
+			expect(await fs.readFile(filePath, "utf8")).toBe(original)
+		})
+
+		it("should refuse to rewrite numbers whose spelling would change", async () => {
+			const filePath = path.join(tmpDir, "version.json")
+			const original = '{\n  "version": [1.0, 2]\n}'
+			await fs.writeFile(filePath, original)
+
+			await expect(formatJson(filePath)).rejects.toMatchObject({ code: "EJSON_LOSSY", line: 2, column: 15 })
+			expect(await fs.readFile(filePath, "utf8")).toBe(original)
+		})
+
+		it("should refuse to drop duplicate keys", async () => {
+			const filePath = path.join(tmpDir, "dup.json")
+			const original = '{"a": {"b": 1, "c": "b", "b": 2}, "b": 3}'
+			await fs.writeFile(filePath, original)
+
+			await expect(formatJson(filePath)).rejects.toMatchObject({ code: "EJSON_LOSSY", line: 1, column: 26 })
+			expect(await fs.readFile(filePath, "utf8")).toBe(original)
+		})
+
+		it("should keep a leading byte order mark", async () => {
+			const filePath = path.join(tmpDir, "bom.json")
+			await fs.writeFile(filePath, '\uFEFF{"a":[1,-2.5,"x\\"y"]}')
+
+			await formatJson(filePath, 0)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe('\uFEFF{"a":[1,-2.5,"x\\"y"]}\n')
+		})
+	})
+
+	describe("readFileForDisplay", () => {
+		it("should expand tabs to the next tab stop based on column position", async () => {
+			const filePath = path.join(tmpDir, "table.txt")
+			await fs.writeFile(filePath, "a\tb\nabc\tb\nabcd\tb\n\tx\ty\n")
+
+			const result = await readFileForDisplay(filePath, 4)
+
+			expect(result).toBe("a   b\nabc b\nabcd    b\n    x   y\n")
+			for (const line of result.split("\n").slice(0, 2)) {
+				expect(line.lastIndexOf("b")).toBe(4)
+			}
+		})
+
+		it("should count multibyte characters as single columns", () => {
+			expect(expandTabs("é\tx", 4)).toBe("é   x")
+			expect(expandTabs("😀\tx", 4)).toBe("😀   x")
+		})
+
+		it("should reset columns after CRLF line breaks", () => {
+			expect(expandTabs("ab\tc\r\n\td", 4)).toBe("ab  c\r\n    d")
+		})
+
+		it("should not modify the file", async () => {
+			const filePath = path.join(tmpDir, "source.ts")
+			await fs.writeFile(filePath, "\tindented\n")
+
+			await readFileForDisplay(filePath, 8)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe("\tindented\n")
+		})
+
+		it("should reject an invalid tab width", async () => {
+			await expect(readFileForDisplay(path.join(tmpDir, "source.ts"), 0)).rejects.toThrow(RangeError)
+		})
+	})
+
+	describe("hasTrailingNewline", () => {
+		it("should detect a final newline", async () => {
+			const lf = path.join(tmpDir, "lf.txt")
+			const crlf = path.join(tmpDir, "crlf.txt")
+			await fs.writeFile(lf, "line\n")
+			await fs.writeFile(crlf, "line\r\n")
+
+			expect(await hasTrailingNewline(lf)).toBe(true)
+			expect(await hasTrailingNewline(crlf)).toBe(true)
+		})
+
+		it("should return false when the last line is unterminated", async () => {
+			const filePath = path.join(tmpDir, "no-newline.txt")
+			await fs.writeFile(filePath, "line\nlast")
+
+			expect(await hasTrailingNewline(filePath)).toBe(false)
+		})
+
+		it("should return false for an empty file", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await hasTrailingNewline(filePath)).toBe(false)
+		})
+	})
+
+	describe("ensureTrailingNewline", () => {
+		it("should append a newline once and be idempotent", async () => {
+			const filePath = path.join(tmpDir, "lf.txt")
+			await fs.writeFile(filePath, "one\ntwo")
+
+			expect(await ensureTrailingNewline(filePath)).toBe(true)
+			expect(await ensureTrailingNewline(filePath)).toBe(false)
+			expect(await fs.readFile(filePath, "utf8")).toBe("one\ntwo\n")
+		})
+
+		it("should append CRLF to a CRLF file", async () => {
+			const filePath = path.join(tmpDir, "crlf.txt")
+			await fs.writeFile(filePath, "one\r\ntwo")
+
+			expect(await ensureTrailingNewline(filePath)).toBe(true)
+			expect(await fs.readFile(filePath, "utf8")).toBe("one\r\ntwo\r\n")
+		})
+
+		it("should detect CRLF from a first line break beyond the first read", async () => {
+			const filePath = path.join(tmpDir, "long-first-line.txt")
+			// The "\r" ends the first 64KB read and the "\n" starts the next one
+			await fs.writeFile(filePath, "x".repeat(64 * 1024 - 1) + "\r\nlast")
+
+			expect(await ensureTrailingNewline(filePath)).toBe(true)
+			expect((await fs.readFile(filePath, "utf8")).endsWith("last\r\n")).toBe(true)
+		})
+
+		it("should leave an empty file unchanged", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await ensureTrailingNewline(filePath)).toBe(false)
+			expect(await fs.readFile(filePath, "utf8")).toBe("")
+		})
+
+		it("should treat a final lone CR as a line break", async () => {
+			const filePath = path.join(tmpDir, "cr.txt")
+			await fs.writeFile(filePath, "a\rb\r")
+
+			expect(await ensureTrailingNewline(filePath)).toBe(false)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\rb\r")
+		})
+	})
+
+	describe("replaceInFileStreaming", () => {
+		it("should replace across a file spanning many stream chunks", async () => {
+			const filePath = path.join(tmpDir, "large.log")
+			const lines = Array.from(
+				{ length: 50_000 },
+				(_, i) => `${i} user=u${i % 7} status=${i % 3 ? "ok" : "fail"}`,
+			)
+			await fs.writeFile(filePath, lines.join("\n") + "\n")
+
+			const replacements = await replaceInFileStreaming(filePath, "status=fail", "status=FAILED")
+
+			const expected = lines.map((line) => line.replace("status=fail", "status=FAILED"))
+			expect(replacements).toBe(Math.ceil(50_000 / 3))
+			expect(await fs.readFile(filePath, "utf8")).toBe(expected.join("\n") + "\n")
+		})
+
+		it("should write in chunks bounded by the flush size plus the longest line", async () => {
+			const filePath = path.join(tmpDir, "bounded.log")
+			const lines = Array.from({ length: 20_000 }, (_, i) => `${i} status=${i % 3 ? "ok" : "fail"}`)
+			await fs.writeFile(filePath, lines.join("\n") + "\n")
+			writtenChunkSizes.length = 0
+
+			await replaceInFileStreaming(filePath, "status=fail", "status=FAILED")
+
+			// 64KB flush size plus the longest rewritten line and its "\n"
+			const bound = 64 * 1024 + Math.max(...lines.map((line) => line.length)) + 3 + 1
+			expect(writtenChunkSizes.length).toBeGreaterThan(1)
+			expect(Math.max(...writtenChunkSizes)).toBeLessThanOrEqual(bound)
+		})
+
+		it("should support capture groups and multiple matches per line", async () => {
+			const filePath = path.join(tmpDir, "ids.txt")
+			await fs.writeFile(filePath, "id=1 id=2\nnone\nid=3")
+
+			expect(await replaceInFileStreaming(filePath, "id=(\\d+)", "id=[$1]")).toBe(3)
+			expect(await fs.readFile(filePath, "utf8")).toBe("id=[1] id=[2]\nnone\nid=[3]")
+		})
+
+		it("should preserve CRLF endings and anchor $ before them", async () => {
+			const filePath = path.join(tmpDir, "crlf.txt")
+			await fs.writeFile(filePath, "a;\r\nb;\r\nc\r\n")
+
+			expect(await replaceInFileStreaming(filePath, ";$", "")).toBe(2)
+			expect(await fs.readFile(filePath, "utf8")).toBe("a\r\nb\r\nc\r\n")
+		})
+
+		it("should not match across line boundaries", async () => {
+			const filePath = path.join(tmpDir, "multiline.txt")
+			await fs.writeFile(filePath, "end\nstart\n")
+
+			expect(await replaceInFileStreaming(filePath, "end\\nstart", "x")).toBe(0)
+			expect(await fs.readFile(filePath, "utf8")).toBe("end\nstart\n")
+		})
+
+		it("should leave the file untouched when nothing matches", async () => {
+			const filePath = path.join(tmpDir, "unchanged.txt")
+			const mtime = new Date("2024-01-01T00:00:00Z")
+			await fs.writeFile(filePath, "alpha\nbeta\n")
+			await fs.utimes(filePath, mtime, mtime)
+			const before = await fs.stat(filePath)
+
+			expect(await replaceInFileStreaming(filePath, "gamma", "delta")).toBe(0)
+
+			const after = await fs.stat(filePath)
+			expect(after.mtimeMs).toBe(before.mtimeMs)
+			expect(after.ino).toBe(before.ino)
+			expect(await fs.readdir(tmpDir)).toEqual(["unchanged.txt"])
+		})
+	})
+
+	describe("normalizeWhitespace", () => {
+		const SOURCE = "  a  b \r\n\tc\t\td\t\n   \nplain\n"
+
+		it.each([
+			[{}, SOURCE, 0],
+			[{ collapseRuns: true }, "  a b \r\n\tc d\t\n   \nplain\n", 2],
+			[{ trimTrailing: true }, "  a  b\r\n\tc\t\td\n\nplain\n", 3],
+			[{ trimLeading: true }, "a  b \r\nc\t\td\t\n\nplain\n", 3],
+			[{ collapseRuns: true, trimTrailing: true }, "  a b\r\n\tc d\n\nplain\n", 3],
+			[{ collapseRuns: true, trimLeading: true }, "a b \r\nc d\t\n\nplain\n", 3],
+			[{ trimTrailing: true, trimLeading: true }, "a  b\r\nc\t\td\n\nplain\n", 3],
+			[{ collapseRuns: true, trimTrailing: true, trimLeading: true }, "a b\r\nc d\n\nplain\n", 3],
+		])("should apply %j", async (options, expected, changed) => {
+			const filePath = path.join(tmpDir, "whitespace.txt")
+			await fs.writeFile(filePath, SOURCE)
+
+			expect(await normalizeWhitespace(filePath, options)).toBe(changed)
+			expect(await fs.readFile(filePath, "utf8")).toBe(expected)
+		})
+
+		it("should leave indentation alone when collapsing runs without trimLeading", async () => {
+			const filePath = path.join(tmpDir, "code.py")
+			await fs.writeFile(filePath, "def f():\n        return  1\n")
+
+			expect(await normalizeWhitespace(filePath, { collapseRuns: true })).toBe(1)
+			expect(await fs.readFile(filePath, "utf8")).toBe("def f():\n        return 1\n")
+		})
+	})
+
+	describe("previewReplaceInFiles", () => {
+		it("should report the same change count as applying the replacement", async () => {
+			const a = path.join(tmpDir, "a.ts")
+			const b = path.join(tmpDir, "b.ts")
+			const c = path.join(tmpDir, "c.ts")
+			await fs.writeFile(a, "const oldName = 1\r\nuse(oldName, oldName)\r\n")
+			await fs.writeFile(b, "nothing here\n")
+			await fs.writeFile(c, "export { oldName }")
+
+			const preview = await previewReplaceInFiles([a, b, c], "\\boldName\\b", "newName")
+
+			expect(await fs.readFile(a, "utf8")).toBe("const oldName = 1\r\nuse(oldName, oldName)\r\n")
+			expect(preview[0]).toEqual({
+				path: a,
+				changeCount: 3,
+				preview: [
+					{ line: 1, before: "const oldName = 1", after: "const newName = 1" },
+					{ line: 2, before: "use(oldName, oldName)", after: "use(newName, newName)" },
+				],
+			})
+			expect(preview[1]).toEqual({ path: b, changeCount: 0, preview: [] })
+
+			for (const entry of preview) {
+				expect(await replaceInFileStreaming(entry.path, "\\boldName\\b", "newName")).toBe(entry.changeCount)
+			}
+		})
+
+		it("should bound the number of preview lines", async () => {
+			const filePath = path.join(tmpDir, "many.txt")
+			await fs.writeFile(filePath, "x\n".repeat(50))
+
+			const [result] = await previewReplaceInFiles([filePath], "x", "y", { maxPreviewLines: 3 })
+
+			expect(result.changeCount).toBe(50)
+			expect(result.preview.map((entry) => entry.line)).toEqual([1, 2, 3])
+		})
+	})
+
+	describe("applyBasicFormatting", () => {
+		const SOURCE = "a  \r\nb\r\nc\t"
+
+		it.each([
+			[{}, SOURCE, 0],
+			[{ trimTrailing: true }, "a\r\nb\r\nc", 2],
+			[{ ensureFinalNewline: true }, "a  \r\nb\r\nc\t\r\n", 1],
+			[{ normalizeLineEndings: "lf" as const }, "a  \nb\nc\t", 2],
+			[{ trimTrailing: true, ensureFinalNewline: true }, "a\r\nb\r\nc\r\n", 2],
+			[
+				{ trimTrailing: true, ensureFinalNewline: true, normalizeLineEndings: "lf" as const },
+				"a\nb\nc\n",
+				3,
+			],
+		])("should apply %j", async (options, expected, linesChanged) => {
+			const filePath = path.join(tmpDir, "format.txt")
+			await fs.writeFile(filePath, SOURCE)
+
+			const result = await applyBasicFormatting(filePath, options)
+
+			expect(result).toEqual({
+				linesChanged,
+				bytesWritten: linesChanged > 0 ? Buffer.byteLength(expected) : 0,
+			})
+			expect(await fs.readFile(filePath, "utf8")).toBe(expected)
+		})
+
+		it("should use LF for the final newline of an LF file and convert to CRLF on request", async () => {
+			const filePath = path.join(tmpDir, "lf.txt")
+			await fs.writeFile(filePath, "x\ny")
+
+			await applyBasicFormatting(filePath, { ensureFinalNewline: true })
+			expect(await fs.readFile(filePath, "utf8")).toBe("x\ny\n")
+
+			await applyBasicFormatting(filePath, { normalizeLineEndings: "crlf" })
+			expect(await fs.readFile(filePath, "utf8")).toBe("x\r\ny\r\n")
+		})
+
+		it("should not add a newline to an empty file", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await applyBasicFormatting(filePath, { ensureFinalNewline: true })).toEqual({
+				linesChanged: 0,
+				bytesWritten: 0,
+			})
+		})
+	})
+
+	describe("wrapLines", () => {
+		it("should wrap a long line at whitespace within the width", async () => {
+			const filePath = path.join(tmpDir, "long.txt")
+			await fs.writeFile(filePath, "the quick brown fox jumps over the lazy dog\n")
+
+			const lines = await wrapLines(filePath, 10)
+
+			expect(lines).toEqual(["the quick", "brown fox", "jumps over", "the lazy", "dog"])
+			expect(lines.every((line) => line.length <= 10)).toBe(true)
+		})
+
+		it("should hard-break words longer than the width", async () => {
+			const filePath = path.join(tmpDir, "word.txt")
+			await fs.writeFile(filePath, "see abcdefghijklmnopqrstuvwxyz now")
+
+			expect(await wrapLines(filePath, 8)).toEqual(["see", "abcdefgh", "ijklmnop", "qrstuvwx", "yz now"])
+		})
+
+		it("should preserve blank lines and short lines", async () => {
+			const filePath = path.join(tmpDir, "blank.txt")
+			await fs.writeFile(filePath, "short\r\n\r\n\talpha beta gamma\r\n")
+
+			expect(await wrapLines(filePath, 11)).toEqual(["short", "", "\talpha beta", "gamma"])
+		})
+
+		it("should keep the indentation of an indented line that is hard-broken", async () => {
+			const filePath = path.join(tmpDir, "indented.txt")
+			await fs.writeFile(filePath, "    abcdefghijklmnop\n    foo barbazqux\n")
+
+			expect(await wrapLines(filePath, 8)).toEqual(["    abcd", "efghijkl", "mnop", "    foo", "barbazqu", "x"])
+		})
+
+		it("should return no lines for an empty file", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await wrapLines(filePath, 8)).toEqual([])
+		})
+
+		it("should reject an invalid width", async () => {
+			const filePath = path.join(tmpDir, "any.txt")
+			await fs.writeFile(filePath, "text\n")
+
+			await expect(wrapLines(filePath, 0)).rejects.toThrow(RangeError)
+			await expect(wrapLines(filePath, 2.5)).rejects.toThrow(RangeError)
+		})
+	})
+})
@@ -0,0 +1,222 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import {
+	chunkContentByTokens,
+	chunkFileByTokens,
+	countTokensInFile,
+	estimateCodeTokens,
+	tokensPerLine,
+	type TokenChunk,
+} from "../token-chunker"
+import { countTextTokens } from "../../../utils/tiktoken"
+
+const SOURCE = Array.from(
+	{ length: 60 },
+	(_, i) => `export const value${i} = computeSomething(${i}, "a string literal number ${i}")`,
+).join("\n")
+
+// Deeply indented, symbol-heavy code, where chars/4 is furthest off
+const NESTED_CODE = [
+	"class Inventory:",
+	"    def restock(self, items):",
+	"        for item in items:",
+	"            if item.quantity < self.threshold:",
+	"                for warehouse in self.warehouses:",
+	"                    if warehouse.has_capacity(item):",
+	"                        warehouse.reserve(item.sku, item.quantity)",
+	"                        break",
+	"                else:",
+	"                    raise RuntimeError(item.sku)",
+	"        return True",
+	"",
+].join("\n")
+
+function expectContiguousCoverage(chunks: TokenChunk[], totalLines: number) {
+	expect(chunks[0].startLine).toBe(1)
+	for (let i = 1; i < chunks.length; i++) {
+		expect(chunks[i].startLine).toBe(chunks[i - 1].endLine + 1)
+	}
+	expect(chunks[chunks.length - 1].endLine).toBe(totalLines)
+}
+
+describe("token-chunker", () => {
+	describe("chunkContentByTokens", () => {
+		it("should keep every chunk within the budget and cover every line", () => {
+			const chunks = chunkContentByTokens(SOURCE, 100)
+
+			expect(chunks.length).toBeGreaterThan(1)
+			for (const chunk of chunks) {
+				expect(chunk.tokens).toBeLessThanOrEqual(100)
+			}
+			expectContiguousCoverage(chunks, 60)
+			expect(chunks.map((chunk) => chunk.content).join("\n")).toBe(SOURCE)
+		})
+
+		it("should stay within the budget when counting with the tokenizer", () => {
+			const chunks = chunkContentByTokens(SOURCE, 80, { useTokenizer: true })
+
+			for (const chunk of chunks) {
+				expect(chunk.tokens).toBeLessThanOrEqual(80)
+				const lineTokens = chunk.content.split("\n").map((line) => countTextTokens(line + "\n"))
+				expect(chunk.tokens).toBe(lineTokens.reduce((sum, tokens) => sum + tokens, 0))
+			}
+			expectContiguousCoverage(chunks, 60)
+		})
+
+		it("should emit a line larger than the budget as its own chunk", () => {
+			const content = ["short", "x".repeat(400), "short again"].join("\n")
+
+			const chunks = chunkContentByTokens(content, 10)
+
+			expect(chunks.map((chunk) => [chunk.startLine, chunk.endLine])).toEqual([
+				[1, 1],
+				[2, 2],
+				[3, 3],
+			])
+			expect(chunks[1].tokens).toBeGreaterThan(10)
+		})
+
+		it("should repeat the last overlapLines of the previous chunk", () => {
+			const chunks = chunkContentByTokens(SOURCE, 100, { overlapLines: 2 })
+
+			expect(chunks.length).toBeGreaterThan(2)
+			for (let i = 1; i < chunks.length; i++) {
+				const previousLines = chunks[i - 1].content.split("\n")
+				const currentLines = chunks[i].content.split("\n")
+
+				expect(chunks[i].startLine).toBe(chunks[i - 1].endLine - 1)
+				expect(currentLines.slice(0, 2)).toEqual(previousLines.slice(-2))
+				expect(chunks[i].endLine).toBeGreaterThan(chunks[i - 1].endLine)
+				expect(chunks[i].tokens).toBeLessThanOrEqual(100)
+			}
+			expect(chunks[0].startLine).toBe(1)
+			expect(chunks[chunks.length - 1].endLine).toBe(60)
+		})
+
+		it("should still advance when a line exceeds the budget with overlap enabled", () => {
+			const content = ["a", "b", "x".repeat(400), "c", "d", "e"].join("\n")
+
+			const chunks = chunkContentByTokens(content, 2, { overlapLines: 3 })
+
+			expect(chunks.map((chunk) => chunk.content)).toContain("x".repeat(400))
+			for (let i = 1; i < chunks.length; i++) {
+				expect(chunks[i].endLine).toBeGreaterThan(chunks[i - 1].endLine)
+				expect(chunks[i].startLine).toBeLessThanOrEqual(chunks[i - 1].endLine + 1)
+			}
+			expect(chunks[chunks.length - 1].endLine).toBe(6)
+		})
+
+		it("should reject a negative overlap", () => {
+			expect(() => chunkContentByTokens(SOURCE, 100, { overlapLines: -1 })).toThrow(RangeError)
+		})
+
+		it("should return no chunks for empty content", () => {
+			expect(chunkContentByTokens("", 100)).toEqual([])
+		})
+
+		it("should reject a budget below 1", () => {
+			expect(() => chunkContentByTokens(SOURCE, 0)).toThrow(RangeError)
+		})
+	})
+
+	describe("estimateCodeTokens", () => {
+		it("should be closer to the tokenizer count than chars/4 on code", () => {
+			const reference = countTextTokens(NESTED_CODE)
+			const codeModeError = Math.abs(estimateCodeTokens(NESTED_CODE) - reference)
+			const heuristicError = Math.abs(Math.ceil(NESTED_CODE.length / 4) - reference)
+
+			expect(codeModeError).toBeLessThan(heuristicError)
+		})
+
+		it("should count each word, symbol run and whitespace run", () => {
+			// "const", " total", " =", " items", ".length", "\n"
+			expect(estimateCodeTokens("const total = items.length\n")).toBe(6)
+			expect(estimateCodeTokens("")).toBe(0)
+		})
+
+		it("should be used for chunking with codeMode", () => {
+			const chunks = chunkContentByTokens(NESTED_CODE, 20, { codeMode: true })
+
+			expectContiguousCoverage(chunks, NESTED_CODE.split("\n").length - 1)
+			for (const chunk of chunks) {
+				const lineTokens = chunk.content.split("\n").map((line) => estimateCodeTokens(line + "\n"))
+				expect(chunk.tokens).toBe(lineTokens.reduce((sum, count) => sum + count, 0))
+				expect(chunk.tokens).toBeLessThanOrEqual(20)
+			}
+		})
+	})
+
+	describe("chunkFileByTokens", () => {
+		let tmpDir: string
+
+		beforeEach(async () => {
+			tmpDir = await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-token-chunker-"))
+		})
+
+		afterEach(async () => {
+			await fs.rm(tmpDir, { recursive: true, force: true })
+		})
+
+		it("should chunk a file's content", async () => {
+			const filePath = path.join(tmpDir, "source.ts")
+			await fs.writeFile(filePath, SOURCE + "\n")
+
+			expect(await chunkFileByTokens(filePath, 100)).toEqual(chunkContentByTokens(SOURCE, 100))
+		})
+
+		it("should return one token count per line", async () => {
+			const filePath = path.join(tmpDir, "source.ts")
+			await fs.writeFile(filePath, SOURCE + "\n")
+
+			const counts = await tokensPerLine(filePath)
+
+			expect(counts).toHaveLength(60)
+			expect(counts[0]).toBe(Math.ceil((SOURCE.split("\n")[0] + "\n").length / 4))
+		})
+
+		it("should sum to roughly the whole-file count with the tokenizer", async () => {
+			const filePath = path.join(tmpDir, "source.ts")
+			await fs.writeFile(filePath, SOURCE + "\n")
+
+			const counts = await tokensPerLine(filePath, { useTokenizer: true })
+			const total = counts.reduce((sum, count) => sum + count, 0)
+			const wholeFile = countTextTokens(SOURCE + "\n")
+
+			expect(counts).toHaveLength(60)
+			expect(Math.abs(total - wholeFile) / wholeFile).toBeLessThan(0.1)
+		})
+
+		it("should count a large file in chunks close to the whole-content count", async () => {
+			const filePath = path.join(tmpDir, "large.ts")
+			// Larger than one stream chunk, so several chunk boundaries are crossed
+			const content = Array.from({ length: 40 }, () => SOURCE).join("\n") + "\n"
+			await fs.writeFile(filePath, content)
+
+			const streamed = await countTokensInFile(filePath, { useTokenizer: true })
+			const wholeFile = countTextTokens(content)
+
+			expect(Math.abs(streamed - wholeFile) / wholeFile).toBeLessThan(0.01)
+			expect(await countTokensInFile(filePath)).toBe(Math.ceil(content.length / 4))
+		})
+
+		it("should count per line and per file with codeMode", async () => {
+			const filePath = path.join(tmpDir, "inventory.py")
+			await fs.writeFile(filePath, NESTED_CODE)
+
+			const counts = await tokensPerLine(filePath, { codeMode: true })
+
+			expect(counts[0]).toBe(estimateCodeTokens("class Inventory:\n"))
+			expect(await countTokensInFile(filePath, { codeMode: true })).toBe(estimateCodeTokens(NESTED_CODE))
+		})
+
+		it("should count an empty file as zero tokens", async () => {
+			const filePath = path.join(tmpDir, "empty.txt")
+			await fs.writeFile(filePath, "")
+
+			expect(await countTokensInFile(filePath, { useTokenizer: true })).toBe(0)
+			expect(await countTokensInFile(filePath)).toBe(0)
+		})
+	})
+})
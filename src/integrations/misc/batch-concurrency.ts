@@ -0,0 +1,28 @@
+/**
+ * Number of files processed at once by batch operations unless configured otherwise
+ */
+export const DEFAULT_BATCH_CONCURRENCY = 16
+
+let batchConcurrency = DEFAULT_BATCH_CONCURRENCY
+
+/**
+ * Sets how many files batch operations (countFileLinesBatch, hashFiles,
+ * readLineRangesBatch, previewReplaceInFiles and searchDirectorySummary)
+ * process at once. Lower it on constrained hosts such as CI runners to avoid
+ * oversubscribing file handles and I/O.
+ *
+ * @param concurrency - Maximum files in flight per batch call, or 0 to restore the default
+ */
+export function setBatchConcurrency(concurrency: number): void {
+	if (!Number.isInteger(concurrency) || concurrency < 0) {
+		throw new RangeError(`Invalid concurrency: ${concurrency}. Concurrency must be a non-negative integer.`)
+	}
+	batchConcurrency = concurrency === 0 ? DEFAULT_BATCH_CONCURRENCY : concurrency
+}
+
+/**
+ * Gets the concurrency batch operations currently use.
+ */
+export function getBatchConcurrency(): number {
+	return batchConcurrency
+}
@@ -0,0 +1,162 @@
+import * as path from "path"
+import fs from "fs/promises"
+import ignore from "ignore"
+import { isBinaryFile } from "isbinaryfile"
+import pLimit from "p-limit"
+
+import { getBatchConcurrency } from "./batch-concurrency"
+import { compilePattern, type TextSearchOptions } from "./text-search"
+
+interface IgnoreScope {
+	/** Directory containing the .gitignore; its patterns are relative to it */
+	base: string
+	rules: ReturnType<typeof ignore>
+}
+
+/**
+ * Checks a path against every .gitignore scope that applies to it. As in git,
+ * the deepest .gitignore with a matching rule decides, so a nested negation
+ * (`!pattern`) can re-include a path ignored by a parent directory's rules.
+ */
+function isIgnoredByScopes(fullPath: string, isDirectory: boolean, scopes: IgnoreScope[]): boolean {
+	for (const { base, rules } of [...scopes].reverse()) {
+		const relative = path.relative(base, fullPath).split(path.sep).join("/")
+		const { ignored, unignored } = rules.test(isDirectory ? `${relative}/` : relative)
+		if (ignored || unignored) {
+			return ignored
+		}
+	}
+	return false
+}
+
+/**
+ * Adds the scope of a directory's own .gitignore, if it has one, to the scopes
+ * inherited from its parents.
+ */
+async function withDirectoryScope(dirPath: string, scopes: IgnoreScope[]): Promise<IgnoreScope[]> {
+	const gitignore = await fs.readFile(path.join(dirPath, ".gitignore"), "utf8").catch(() => undefined)
+	return gitignore === undefined ? scopes : [...scopes, { base: dirPath, rules: ignore().add(gitignore) }]
+}
+
+/**
+ * Recursively lists files under a directory, honoring the .gitignore files found
+ * along the way (each scoped to its own directory) and skipping .git.
+ */
+async function walkFiles(dirPath: string, scopes: IgnoreScope[] = []): Promise<string[]> {
+	const entries = await fs.readdir(dirPath, { withFileTypes: true })
+	const activeScopes = await withDirectoryScope(dirPath, scopes)
+
+	const files: string[] = []
+	for (const entry of entries) {
+		const fullPath = path.join(dirPath, entry.name)
+		if (entry.name === ".git" || isIgnoredByScopes(fullPath, entry.isDirectory(), activeScopes)) {
+			continue
+		}
+
+		if (entry.isDirectory()) {
+			files.push(...(await walkFiles(fullPath, activeScopes)))
+		} else if (entry.isFile()) {
+			files.push(fullPath)
+		}
+	}
+	return files
+}
+
+/**
+ * Checks whether git would ignore a path, applying the .gitignore files of the
+ * root and of every directory between it and the path, each scoped to its own
+ * directory. A path inside an ignored directory is ignored too. The path does
+ * not need to exist; it is treated as a file.
+ *
+ * @param rootPath - Repository root whose .gitignore rules apply
+ * @param filePath - Path to check, absolute or relative to the root
+ * @returns Promise resolving to true if the path is ignored; false when no rule matches or it is outside the root
+ */
+export async function isIgnored(rootPath: string, filePath: string): Promise<boolean> {
+	const root = path.resolve(rootPath)
+	const relative = path.relative(root, path.resolve(root, filePath))
+	if (relative === "" || relative.startsWith("..") || path.isAbsolute(relative)) {
+		return false
+	}
+
+	const segments = relative.split(path.sep)
+	let dirPath = root
+	let scopes = await withDirectoryScope(root, [])
+
+	for (const [index, segment] of segments.entries()) {
+		const fullPath = path.join(dirPath, segment)
+		const isDirectory = index < segments.length - 1
+		if (isIgnoredByScopes(fullPath, isDirectory, scopes)) {
+			return true
+		}
+		if (isDirectory) {
+			dirPath = fullPath
+			scopes = await withDirectoryScope(dirPath, scopes)
+		}
+	}
+	return false
+}
+
+export interface DirectorySearchSummary {
+	/** Total number of matches across all files */
+	totalMatches: number
+	/** Number of files with at least one match */
+	fileCount: number
+	/** Per-file match counts for files with matches, sorted by path (relative to the root, "/"-separated) */
+	files: Array<{ path: string; count: number }>
+}
+
+/**
+ * Counts regex matches in every file under a directory without returning match
+ * content, for "N matches in M files" summaries. Files excluded by .gitignore
+ * and binary files are skipped. The pattern is applied to each line separately,
+ * as in findSpans, so matches never span lines and `^` and `$` anchor at line
+ * boundaries.
+ *
+ * @param rootPath - Directory to search
+ * @param pattern - Regular expression source
+ * @param options - Search options
+ * @returns Promise resolving to the total and per-file match counts
+ */
+export async function searchDirectorySummary(
+	rootPath: string,
+	pattern: string,
+	options: TextSearchOptions = {},
+): Promise<DirectorySearchSummary> {
+	const regex = compilePattern(pattern, options)
+	const filePaths = await walkFiles(rootPath)
+	const limit = pLimit(getBatchConcurrency())
+
+	const counts = await Promise.all(
+		filePaths.map((filePath) =>
+			limit(async () => {
+				if (await isBinaryFile(filePath).catch(() => true)) {
+					return 0
+				}
+
+				const lines = (await fs.readFile(filePath, "utf8")).split(/\r?\n/)
+				let count = 0
+				for (const line of lines) {
+					for (const match of line.matchAll(regex)) {
+						if (match[0].length > 0) count++
+					}
+				}
+				return count
+			}),
+		),
+	)
+
+	const files = filePaths
+		.map((filePath, index) => ({
+			path: path.relative(rootPath, filePath).split(path.sep).join("/"),
+			count: counts[index],
+		}))
+		.filter((file) => file.count > 0)
+		.sort((a, b) => a.path.localeCompare(b.path))
+
+	return {
+		totalMatches: files.reduce((sum, file) => sum + file.count, 0),
+		fileCount: files.length,
+		files,
+	}
+}
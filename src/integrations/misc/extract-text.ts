@@ -133,7 +133,7 @@ export async function extractTextFromFile(filePath: string): Promise<string> {
 	return result.content
 }
 
-export function addLineNumbers(content: string, startLine: number = 1): string {
+export function addLineNumbers(content: string, startLine: number = 1, padWidth?: number): string {
 	// If content is empty, return empty string - empty files should not have line numbers
 	// If content is empty but startLine > 1, return "startLine | " because we know the file is not empty
 	// but the content is empty at that line offset
@@ -148,7 +148,7 @@ export function addLineNumbers(content: string, startLine: number = 1): string {
 		lines.pop()
 	}
 
-	const maxLineNumberWidth = String(startLine + lines.length - 1).length
+	const maxLineNumberWidth = padWidth ?? String(startLine + lines.length - 1).length
 	const numberedContent = lines
 		.map((line, index) => {
 			const lineNumber = String(startLine + index).padStart(maxLineNumberWidth, " ")
@@ -158,6 +158,116 @@ export function addLineNumbers(content: string, startLine: number = 1): string {
 
 	return numberedContent + "\n"
 }
+
+/**
+ * Reads a file and prefixes each line with its 1-based line number, using the same
+ * "N | content" format that addLineNumbers produces for the model.
+ *
+ * @param filePath - Path to the file to read
+ * @param startLine - Optional. 1-based line to start from (default: 1). Lines keep their position in the file.
+ * @param padWidth - Optional. Width to pad line numbers to. Defaults to the digit count of the file's total line count.
+ * @returns Promise resolving to the numbered content, or an empty string when there are no lines to show
+ * @throws {RangeError} If padWidth is not a non-negative integer
+ */
+export async function readFileWithLineNumbers(
+	filePath: string,
+	startLine: number = 1,
+	padWidth?: number,
+): Promise<string> {
+	if (padWidth !== undefined && (!Number.isInteger(padWidth) || padWidth < 0)) {
+		throw new RangeError(`Invalid padWidth: ${padWidth}. Pad width must be a non-negative integer.`)
+	}
+
+	const content = await fs.readFile(filePath, "utf8")
+
+	if (content === "") {
+		return ""
+	}
+
+	const lines = content.split("\n")
+	if (lines[lines.length - 1] === "") {
+		lines.pop()
+	}
+
+	const firstLine = Math.max(1, Math.floor(startLine))
+	if (firstLine > lines.length) {
+		return ""
+	}
+
+	const width = padWidth ?? String(lines.length).length
+	return addLineNumbers(lines.slice(firstLine - 1).join("\n") + "\n", firstLine, width)
+}
+
+const LICENSE_KEYWORDS = /copyright|licen[cs]e|spdx-license-identifier|\(c\)|all rights reserved/i
+const LINE_COMMENT_PREFIXES = ["//", "#", "--", ";"]
+
+/**
+ * Finds the end (exclusive) of a comment block starting at `start`, either a
+ * block comment or a run of line comments, or returns `start` if there is none.
+ */
+function findLeadingCommentEnd(lines: string[], start: number): number {
+	const first = lines[start]?.trim() ?? ""
+
+	for (const [open, close] of [
+		["/*", "*/"],
+		["<!--", "-->"],
+	]) {
+		if (first.startsWith(open)) {
+			const closeIndex = lines.findIndex((line, index) =>
+				index === start ? line.indexOf(close, line.indexOf(open) + open.length) !== -1 : line.includes(close),
+			)
+			return closeIndex >= start ? closeIndex + 1 : start
+		}
+	}
+
+	const prefix = LINE_COMMENT_PREFIXES.find((candidate) => first.startsWith(candidate) && !first.startsWith("#!"))
+	if (!prefix) {
+		return start
+	}
+
+	let end = start
+	while (end < lines.length && lines[end].trim().startsWith(prefix)) {
+		end++
+	}
+	return end
+}
+
+/**
+ * Reads the first lines of a file for a preview, skipping a leading license or
+ * copyright header (a block or line comment mentioning a license or copyright)
+ * and the blank lines after it. Files without such a header are previewed from
+ * the top.
+ *
+ * @param filePath - Path to the file to preview
+ * @param maxLines - Maximum number of lines to return
+ * @returns Promise resolving to up to `maxLines` lines joined with "\n"
+ * @throws {RangeError} If maxLines is not a positive integer
+ */
+export async function getContentPreview(filePath: string, maxLines: number): Promise<string> {
+	if (!Number.isInteger(maxLines) || maxLines < 1) {
+		throw new RangeError(`Invalid maxLines: ${maxLines}. maxLines must be a positive integer.`)
+	}
+
+	const lines = (await fs.readFile(filePath, "utf8")).split(/\r?\n/)
+
+	let start = lines.findIndex((line) => line.trim() !== "")
+	if (start === -1) {
+		return ""
+	}
+
+	const headerEnd = findLeadingCommentEnd(lines, start)
+	if (headerEnd > start && LICENSE_KEYWORDS.test(lines.slice(start, headerEnd).join("\n"))) {
+		start = headerEnd
+		while (start < lines.length && lines[start].trim() === "") {
+			start++
+		}
+	} else {
+		start = 0
+	}
+
+	return lines.slice(start, start + maxLines).join("\n")
+}
+
 // Checks if every line in the content has line numbers prefixed (e.g., "1 | content" or "123 | content")
 // Line numbers must be followed by a single pipe character (not double pipes)
 export function everyLineHasLineNumbers(content: string): boolean {
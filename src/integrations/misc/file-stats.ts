@@ -0,0 +1,440 @@
+import { createHash } from "crypto"
+import { createReadStream } from "fs"
+import fs from "fs/promises"
+import pLimit from "p-limit"
+
+import { getBatchConcurrency } from "./batch-concurrency"
+import { readMagicBytes } from "./file-type"
+import { detectIndentSize } from "./indentation-reader"
+
+/**
+ * Computes the frequency of every byte value in a file in a single streaming pass.
+ *
+ * @param filePath - Path to the file to analyze
+ * @returns Promise resolving to a 256-element array where index `b` holds the count of byte `b`
+ */
+export async function getByteHistogram(filePath: string): Promise<number[]> {
+	const histogram = new Array<number>(256).fill(0)
+
+	for await (const chunk of createReadStream(filePath)) {
+		for (const byte of chunk as Buffer) {
+			histogram[byte]++
+		}
+	}
+
+	return histogram
+}
+
+/**
+ * Computes Shannon entropy in bits per byte from a byte histogram.
+ *
+ * @param histogram - Byte frequencies as returned by getByteHistogram
+ * @returns Entropy between 0 (a single repeated byte or empty input) and 8 (uniformly random bytes)
+ */
+export function entropyFromHistogram(histogram: number[]): number {
+	const total = histogram.reduce((sum, count) => sum + count, 0)
+	if (total === 0) {
+		return 0
+	}
+
+	let entropy = 0
+	for (const count of histogram) {
+		if (count > 0) {
+			const p = count / total
+			entropy -= p * Math.log2(p)
+		}
+	}
+	return entropy
+}
+
+/**
+ * Computes the Shannon entropy of a file's bytes. Values close to 8 bits per byte
+ * indicate compressed or encrypted data; source code and prose are typically below 5.
+ *
+ * @param filePath - Path to the file to analyze
+ * @returns Promise resolving to the entropy in bits per byte
+ */
+export async function getEntropy(filePath: string): Promise<number> {
+	return entropyFromHistogram(await getByteHistogram(filePath))
+}
+
+export interface LineEndingCounts {
+	/** Lines ending in "\n" alone */
+	lf: number
+	/** Lines ending in "\r\n" */
+	crlf: number
+	/** Lines ending in a lone "\r" */
+	cr: number
+}
+
+/**
+ * Adds the line terminators in `bytes` to `counts`. A trailing CR is reported
+ * back instead of counted, so a CRLF split across chunks is still counted once.
+ *
+ * @returns Whether the chunk ended in a CR that is still pending
+ */
+function tallyLineEndings(bytes: Buffer, counts: LineEndingCounts, pendingCr: boolean): boolean {
+	for (const byte of bytes) {
+		if (byte === 0x0a) {
+			if (pendingCr) {
+				counts.crlf++
+			} else {
+				counts.lf++
+			}
+			pendingCr = false
+		} else {
+			if (pendingCr) {
+				counts.cr++
+			}
+			pendingCr = byte === 0x0d
+		}
+	}
+	return pendingCr
+}
+
+/**
+ * Counts each kind of line terminator in a file in a single streaming pass, so
+ * tooling can report mixed endings (e.g. "90% LF, 10% CRLF") rather than just
+ * the dominant style.
+ *
+ * @param filePath - Path to the file to analyze
+ * @returns Promise resolving to the number of LF, CRLF and lone CR terminators
+ */
+export async function getLineEndingCounts(filePath: string): Promise<LineEndingCounts> {
+	const counts: LineEndingCounts = { lf: 0, crlf: 0, cr: 0 }
+	let pendingCr = false
+
+	for await (const chunk of createReadStream(filePath)) {
+		pendingCr = tallyLineEndings(chunk as Buffer, counts, pendingCr)
+	}
+
+	if (pendingCr) {
+		counts.cr++
+	}
+	return counts
+}
+
+export type IndentType = "tabs" | "spaces" | "none"
+
+/**
+ * Detects whether a file is indented with tabs or spaces by counting which one
+ * starts more indented lines. Ties go to spaces.
+ *
+ * @param content - The file content to analyze
+ * @returns "tabs", "spaces", or "none" if no line is indented
+ */
+export function detectIndentType(content: string): IndentType {
+	let tabs = 0
+	let spaces = 0
+
+	for (const line of content.split("\n")) {
+		if (line.trim().length === 0) continue
+		if (line[0] === "\t") tabs++
+		else if (line[0] === " ") spaces++
+	}
+
+	if (tabs === 0 && spaces === 0) {
+		return "none"
+	}
+	return tabs > spaces ? "tabs" : "spaces"
+}
+
+export interface IndentationIssue {
+	/** 1-based line number */
+	line: number
+	/** "mixed" if the indentation has both tabs and spaces; "minority" if it only uses the less common style */
+	type: "mixed" | "minority"
+}
+
+/**
+ * Finds lines whose indentation is inconsistent with the rest of the file:
+ * indentation mixing tabs and spaces, or indentation using only the style
+ * other than the dominant one (as determined by detectIndentType).
+ *
+ * @param filePath - Path to the file to check
+ * @returns Promise resolving to the offending lines in order
+ */
+export async function detectMixedIndentation(filePath: string): Promise<IndentationIssue[]> {
+	const content = await fs.readFile(filePath, "utf8")
+	const dominant = detectIndentType(content)
+	const issues: IndentationIssue[] = []
+
+	content.split("\n").forEach((line, index) => {
+		if (line.trim().length === 0) return
+
+		const indentation = line.match(/^[ \t]*/)![0]
+		const hasTabs = indentation.includes("\t")
+		const hasSpaces = indentation.includes(" ")
+
+		if (hasTabs && hasSpaces) {
+			issues.push({ line: index + 1, type: "mixed" })
+		} else if ((hasTabs && dominant === "spaces") || (hasSpaces && dominant === "tabs")) {
+			issues.push({ line: index + 1, type: "minority" })
+		}
+	})
+
+	return issues
+}
+
+export interface FileContentWithMeta {
+	content: string
+	/** Number of lines, counting a final unterminated line and treating LF, CRLF and lone CR as breaks */
+	lineCount: number
+	indentType: IndentType
+	/** Most common indentation step in columns (see detectIndentSize) */
+	indentSize: number
+	/** Dominant line terminator, or "none" for files without one */
+	lineEnding: keyof LineEndingCounts | "none"
+	hasTrailingNewline: boolean
+}
+
+/**
+ * Reads a file once and returns its content together with the structural
+ * metadata an editor needs when opening it, instead of making a separate call
+ * (and read) for each property. The values match the single-purpose helpers:
+ * countFileLines, detectIndentType, detectIndentSize, getLineEndingCounts and
+ * hasTrailingNewline.
+ *
+ * @param filePath - Path to the file to read
+ * @returns Promise resolving to the content and its metadata
+ */
+export async function readFileContentWithMeta(filePath: string): Promise<FileContentWithMeta> {
+	const data = await fs.readFile(filePath)
+	const content = data.toString("utf8")
+
+	const counts: LineEndingCounts = { lf: 0, crlf: 0, cr: 0 }
+	if (tallyLineEndings(data, counts, false)) {
+		counts.cr++
+	}
+
+	const terminators = counts.lf + counts.crlf + counts.cr
+	const lastByte = data[data.length - 1]
+	const endsWithTerminator = lastByte === 0x0a || lastByte === 0x0d
+
+	let lineEnding: FileContentWithMeta["lineEnding"] = "none"
+	if (terminators > 0) {
+		// Ties prefer LF, then CRLF
+		lineEnding = (["lf", "crlf", "cr"] as const).reduce((best, kind) => (counts[kind] > counts[best] ? kind : best))
+	}
+
+	return {
+		content,
+		lineCount: terminators + (data.length > 0 && !endsWithTerminator ? 1 : 0),
+		indentType: detectIndentType(content),
+		indentSize: detectIndentSize(content),
+		lineEnding,
+		hasTrailingNewline: lastByte === 0x0a,
+	}
+}
+
+/** Maximum number of files kept by readFileContentCached */
+const FILE_CACHE_MAX_ENTRIES = 100
+
+/** Maximum total content length kept by readFileContentCached */
+const FILE_CACHE_MAX_BYTES = 32 * 1024 * 1024
+
+/** Files larger than this are read by readFileContentCached without being cached */
+const FILE_CACHE_MAX_FILE_BYTES = 8 * 1024 * 1024
+
+interface CachedFileContent {
+	mtimeMs: number
+	size: number
+	content: string
+}
+
+// Map iteration order doubles as recency order: hits are re-inserted at the end
+const fileContentCache = new Map<string, CachedFileContent>()
+
+/** Sum of `content.length` over fileContentCache */
+let fileContentCacheBytes = 0
+
+function evictCachedFile(filePath: string): void {
+	const cached = fileContentCache.get(filePath)
+	if (cached) {
+		fileContentCacheBytes -= cached.content.length
+		fileContentCache.delete(filePath)
+	}
+}
+
+/**
+ * Reads a file as UTF-8, reusing the previous result while the file's mtime and
+ * size are unchanged. Entries are kept in an LRU keyed by path and bounded by
+ * both entry count and total content length; files above a per-file size limit
+ * are always read from disk.
+ *
+ * @param filePath - Path to the file to read
+ * @returns Promise resolving to the file content
+ */
+export async function readFileContentCached(filePath: string): Promise<string> {
+	const stats = await fs.stat(filePath)
+	const cached = fileContentCache.get(filePath)
+
+	if (cached && cached.mtimeMs === stats.mtimeMs && cached.size === stats.size) {
+		fileContentCache.delete(filePath)
+		fileContentCache.set(filePath, cached)
+		return cached.content
+	}
+
+	const content = await fs.readFile(filePath, "utf8")
+
+	evictCachedFile(filePath)
+	if (stats.size > FILE_CACHE_MAX_FILE_BYTES) {
+		return content
+	}
+
+	fileContentCache.set(filePath, { mtimeMs: stats.mtimeMs, size: stats.size, content })
+	fileContentCacheBytes += content.length
+	while (fileContentCache.size > FILE_CACHE_MAX_ENTRIES || fileContentCacheBytes > FILE_CACHE_MAX_BYTES) {
+		evictCachedFile(fileContentCache.keys().next().value!)
+	}
+
+	return content
+}
+
+/**
+ * Empties the cache used by readFileContentCached.
+ */
+export function clearFileCache(): void {
+	fileContentCache.clear()
+	fileContentCacheBytes = 0
+}
+
+export interface CsvShape {
+	/** Number of records, including the header row; quoted newlines don't start a record */
+	rows: number
+	/** Number of fields in the first record */
+	columns: number
+	/** Whether any record has a different number of fields than the first */
+	hasInconsistentColumns: boolean
+}
+
+/**
+ * Measures the shape of a CSV or TSV file. Fields are parsed per RFC 4180, so
+ * quoted fields may contain delimiters, doubled quotes and line breaks. A quote
+ * only opens a quoted field as the field's first character; elsewhere it is
+ * literal text. Blank lines are ignored.
+ *
+ * @param filePath - Path to the file to analyze
+ * @param delimiter - Field delimiter, e.g. "," or "\t" (default: ",")
+ * @returns Promise resolving to the row and column counts
+ */
+export async function getCsvShape(filePath: string, delimiter: string = ","): Promise<CsvShape> {
+	if (delimiter.length !== 1 || delimiter === '"' || delimiter === "\n" || delimiter === "\r") {
+		throw new RangeError(`Invalid delimiter: ${JSON.stringify(delimiter)}. Delimiter must be a single character.`)
+	}
+
+	const content = await fs.readFile(filePath, "utf8")
+	const shape: CsvShape = { rows: 0, columns: 0, hasInconsistentColumns: false }
+
+	let fields = 1
+	let recordIsEmpty = true
+	let inQuotes = false
+	let atFieldStart = true
+
+	const endRecord = () => {
+		if (!recordIsEmpty) {
+			if (shape.rows === 0) {
+				shape.columns = fields
+			} else if (fields !== shape.columns) {
+				shape.hasInconsistentColumns = true
+			}
+			shape.rows++
+		}
+		fields = 1
+		recordIsEmpty = true
+		atFieldStart = true
+	}
+
+	for (let index = 0; index < content.length; index++) {
+		const char = content[index]
+
+		if (inQuotes) {
+			if (char === '"' && content[index + 1] === '"') {
+				index++
+			} else if (char === '"') {
+				inQuotes = false
+			}
+		} else if (char === '"' && atFieldStart) {
+			inQuotes = true
+			recordIsEmpty = false
+			atFieldStart = false
+		} else if (char === delimiter) {
+			fields++
+			recordIsEmpty = false
+			atFieldStart = true
+		} else if (char === "\n" || char === "\r") {
+			if (char === "\r" && content[index + 1] === "\n") {
+				index++
+			}
+			endRecord()
+		} else {
+			recordIsEmpty = false
+			atFieldStart = false
+		}
+	}
+	endRecord()
+
+	return shape
+}
+
+/**
+ * Computes the digest of a file's content by streaming it through the hash.
+ *
+ * @param filePath - Path to the file to hash
+ * @param algorithm - Any algorithm supported by crypto.createHash (default: "sha256")
+ * @returns Promise resolving to the hex digest
+ */
+export async function hashFile(filePath: string, algorithm: string = "sha256"): Promise<string> {
+	const hash = createHash(algorithm)
+	for await (const chunk of createReadStream(filePath)) {
+		hash.update(chunk as Buffer)
+	}
+	return hash.digest("hex")
+}
+
+/**
+ * Hashes many files concurrently (see setBatchConcurrency), e.g. to find duplicate
+ * attachments.
+ *
+ * @param filePaths - Paths of the files to hash
+ * @param algorithm - Any algorithm supported by crypto.createHash (default: "sha256")
+ * @returns Promise resolving to each file's hex digest in input order ("" for files that can't be read)
+ */
+export async function hashFiles(filePaths: string[], algorithm: string = "sha256"): Promise<string[]> {
+	// Fail fast on an unknown algorithm instead of reporting every file as unreadable
+	createHash(algorithm)
+
+	const limit = pLimit(getBatchConcurrency())
+	return Promise.all(filePaths.map((filePath) => limit(() => hashFile(filePath, algorithm).catch(() => ""))))
+}
+
+/** Number of leading bytes isMinified inspects */
+const MINIFIED_SAMPLE_SIZE = 64 * 1024
+
+/** Average line length (in characters) above which content may be minified */
+const MINIFIED_MIN_AVERAGE_LINE_LENGTH = 200
+
+/** Share of whitespace characters below which long-lined content is considered minified */
+const MINIFIED_MAX_WHITESPACE_RATIO = 0.1
+
+/**
+ * Guesses whether a file is minified (e.g. bundled JS or CSS), so it can be
+ * offered for formatting rather than shown as-is. Minified content has very
+ * long lines and little whitespace; long-lined prose still has plenty of
+ * spaces and is not flagged. Only the start of the file is inspected.
+ *
+ * @param filePath - Path to the file to check
+ * @returns Promise resolving to true if the file looks minified
+ */
+export async function isMinified(filePath: string): Promise<boolean> {
+	const sample = (await readMagicBytes(filePath, MINIFIED_SAMPLE_SIZE)).toString("utf8")
+	if (sample.trim().length === 0) {
+		return false
+	}
+
+	const lines = sample.split("\n").filter((line) => line.trim().length > 0)
+	const averageLineLength = lines.reduce((sum, line) => sum + line.length, 0) / lines.length
+	const whitespaceRatio = (sample.match(/\s/g)?.length ?? 0) / sample.length
+
+	return averageLineLength >= MINIFIED_MIN_AVERAGE_LINE_LENGTH && whitespaceRatio < MINIFIED_MAX_WHITESPACE_RATIO
+}
@@ -0,0 +1,314 @@
+import * as path from "path"
+import fs from "fs/promises"
+import { isBinaryFile } from "isbinaryfile"
+
+import { IMAGE_MIME_TYPES } from "../../core/tools/helpers/imageHelpers"
+
+/**
+ * Number of leading bytes read when sniffing a file's type
+ */
+const SNIFF_LENGTH = 512
+
+interface MagicSignature {
+	mime: string
+	/** Byte offset at which the signature starts */
+	offset?: number
+	bytes: number[] | string
+	/** Optional extra check for containers that share a prefix (RIFF, ftyp, ...) */
+	matches?: (header: Buffer) => boolean
+}
+
+const ascii = (header: Buffer, offset: number, text: string) =>
+	header.length >= offset + text.length && header.toString("latin1", offset, offset + text.length) === text
+
+const ftypBrand = (header: Buffer) => (ascii(header, 4, "ftyp") ? header.toString("latin1", 8, 12) : undefined)
+
+/**
+ * Magic-number signatures, checked in order. More specific entries must come
+ * before entries that share a prefix with them.
+ */
+const MAGIC_SIGNATURES: MagicSignature[] = [
+	{ mime: "image/png", bytes: [0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a] },
+	{ mime: "image/jpeg", bytes: [0xff, 0xd8, 0xff] },
+	{ mime: "image/gif", bytes: "GIF8" },
+	{ mime: "image/webp", bytes: "RIFF", matches: (header) => ascii(header, 8, "WEBP") },
+	{ mime: "audio/wav", bytes: "RIFF", matches: (header) => ascii(header, 8, "WAVE") },
+	{ mime: "video/x-msvideo", bytes: "RIFF", matches: (header) => ascii(header, 8, "AVI ") },
+	{ mime: "image/bmp", bytes: "BM", matches: (header) => header.length >= 14 && header.readUInt32LE(6) === 0 },
+	{ mime: "image/x-icon", bytes: [0x00, 0x00, 0x01, 0x00] },
+	{ mime: "image/tiff", bytes: [0x49, 0x49, 0x2a, 0x00] },
+	{ mime: "image/tiff", bytes: [0x4d, 0x4d, 0x00, 0x2a] },
+	{ mime: "image/avif", bytes: [], matches: (header) => ["avif", "avis"].includes(ftypBrand(header) ?? "") },
+	{ mime: "image/heic", bytes: [], matches: (header) => ["heic", "heix", "mif1"].includes(ftypBrand(header) ?? "") },
+	{ mime: "video/quicktime", bytes: [], matches: (header) => ftypBrand(header) === "qt  " },
+	{ mime: "audio/mp4", bytes: [], matches: (header) => ftypBrand(header) === "M4A " },
+	{ mime: "video/mp4", bytes: [], matches: (header) => ftypBrand(header) !== undefined },
+	{ mime: "video/webm", bytes: [0x1a, 0x45, 0xdf, 0xa3] },
+	{ mime: "application/pdf", bytes: "%PDF-" },
+	{ mime: "application/zip", bytes: [0x50, 0x4b, 0x03, 0x04] },
+	{ mime: "application/zip", bytes: [0x50, 0x4b, 0x05, 0x06] },
+	{ mime: "application/gzip", bytes: [0x1f, 0x8b] },
+	{ mime: "application/x-bzip2", bytes: "BZh" },
+	{ mime: "application/x-xz", bytes: [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00] },
+	{ mime: "application/zstd", bytes: [0x28, 0xb5, 0x2f, 0xfd] },
+	{ mime: "application/x-7z-compressed", bytes: [0x37, 0x7a, 0xbc, 0xaf, 0x27, 0x1c] },
+	{ mime: "application/vnd.rar", bytes: "Rar!\x1a\x07" },
+	{ mime: "application/x-tar", offset: 257, bytes: "ustar" },
+	{ mime: "audio/mpeg", bytes: "ID3" },
+	{ mime: "audio/mpeg", bytes: [0xff, 0xfb] },
+	{ mime: "audio/ogg", bytes: "OggS" },
+	{ mime: "audio/flac", bytes: "fLaC" },
+	{ mime: "application/wasm", bytes: [0x00, 0x61, 0x73, 0x6d] },
+	{ mime: "application/x-elf", bytes: [0x7f, 0x45, 0x4c, 0x46] },
+	{ mime: "application/x-msdownload", bytes: "MZ" },
+	{ mime: "application/vnd.sqlite3", bytes: "SQLite format 3\x00" },
+]
+
+/**
+ * Extension fallbacks for files whose content has no recognizable signature
+ */
+const EXTENSION_MIME_TYPES: Record<string, string> = {
+	...IMAGE_MIME_TYPES,
+	".pdf": "application/pdf",
+	".zip": "application/zip",
+	".gz": "application/gzip",
+	".tar": "application/x-tar",
+	".mp3": "audio/mpeg",
+	".wav": "audio/wav",
+	".mp4": "video/mp4",
+	".mov": "video/quicktime",
+	".webm": "video/webm",
+	".json": "application/json",
+	".xml": "application/xml",
+	".html": "text/html",
+	".htm": "text/html",
+	".css": "text/css",
+	".csv": "text/csv",
+	".md": "text/markdown",
+	".js": "text/javascript",
+	".mjs": "text/javascript",
+	".docx": "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+	".xlsx": "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+	".ipynb": "application/x-ipynb+json",
+}
+
+/**
+ * Reads the first bytes of a file for magic-number sniffing, without reading
+ * the rest of the file. Shorter files return fewer bytes.
+ *
+ * @param filePath - Path to the file to read
+ * @param count - Maximum number of bytes to read
+ * @returns Promise resolving to up to `count` leading bytes
+ */
+export async function readMagicBytes(filePath: string, count: number): Promise<Buffer> {
+	if (!Number.isInteger(count) || count < 0) {
+		throw new RangeError(`Invalid count: ${count}. Count must be a non-negative integer.`)
+	}
+
+	const handle = await fs.open(filePath, "r")
+	try {
+		// Size the buffer by the file so a large count doesn't allocate memory a short file can't fill
+		const { size } = await handle.stat()
+		const buffer = Buffer.alloc(Math.min(count, size))
+		const { bytesRead } = await handle.read(buffer, 0, buffer.length, 0)
+		return buffer.subarray(0, bytesRead)
+	} finally {
+		await handle.close()
+	}
+}
+
+/**
+ * Detects a MIME type from a file header by matching known magic numbers.
+ *
+ * @param header - Leading bytes of a file
+ * @returns The detected MIME type, or undefined when no signature matches
+ */
+export function detectMimeTypeFromBuffer(header: Buffer): string | undefined {
+	for (const signature of MAGIC_SIGNATURES) {
+		const offset = signature.offset ?? 0
+		const expected =
+			typeof signature.bytes === "string" ? Buffer.from(signature.bytes, "latin1") : Buffer.from(signature.bytes)
+
+		if (header.length < offset + expected.length) continue
+		if (!header.subarray(offset, offset + expected.length).equals(expected)) continue
+		if (signature.matches && !signature.matches(header)) continue
+
+		return signature.mime
+	}
+	return undefined
+}
+
+/**
+ * Detects the MIME type of an arbitrary file.
+ *
+ * Content is sniffed first (images, PDFs, archives, audio/video, executables),
+ * then the extension is consulted. Remaining non-binary files are reported as
+ * "text/plain" and everything else as "application/octet-stream".
+ *
+ * @param filePath - Path to the file
+ * @returns Promise resolving to the MIME type
+ */
+export async function detectMimeType(filePath: string): Promise<string> {
+	const sniffed = detectMimeTypeFromBuffer(await readMagicBytes(filePath, SNIFF_LENGTH))
+	if (sniffed) {
+		return sniffed
+	}
+
+	const byExtension = EXTENSION_MIME_TYPES[path.extname(filePath).toLowerCase()]
+	if (byExtension) {
+		return byExtension
+	}
+
+	const isBinary = await isBinaryFile(filePath).catch(() => true)
+	return isBinary ? "application/octet-stream" : "text/plain"
+}
+
+export type FileTypeCategory = "image" | "video" | "audio" | "archive" | "document" | "code" | "text" | "binary"
+
+const ARCHIVE_MIME_TYPES = new Set([
+	"application/zip",
+	"application/gzip",
+	"application/x-tar",
+	"application/x-bzip2",
+	"application/x-xz",
+	"application/zstd",
+	"application/x-7z-compressed",
+	"application/vnd.rar",
+])
+
+/**
+ * Extensions classified as documents even when their content sniffs as
+ * something else (Office formats are zip containers)
+ */
+const DOCUMENT_EXTENSIONS = new Set([
+	".pdf",
+	".doc",
+	".docx",
+	".odt",
+	".rtf",
+	".xls",
+	".xlsx",
+	".ods",
+	".ppt",
+	".pptx",
+	".odp",
+	".epub",
+	".ipynb",
+])
+
+const CODE_EXTENSIONS = new Set([
+	".js",
+	".mjs",
+	".cjs",
+	".jsx",
+	".ts",
+	".mts",
+	".cts",
+	".tsx",
+	".vue",
+	".svelte",
+	".py",
+	".rs",
+	".go",
+	".c",
+	".h",
+	".cpp",
+	".cc",
+	".hpp",
+	".cs",
+	".rb",
+	".java",
+	".php",
+	".swift",
+	".sol",
+	".kt",
+	".kts",
+	".scala",
+	".ex",
+	".exs",
+	".el",
+	".ml",
+	".mli",
+	".lua",
+	".zig",
+	".elm",
+	".vb",
+	".dart",
+	".r",
+	".pl",
+	".sh",
+	".bash",
+	".zsh",
+	".ps1",
+	".sql",
+	".html",
+	".htm",
+	".css",
+	".scss",
+	".less",
+	".json",
+	".yaml",
+	".yml",
+	".toml",
+	".xml",
+])
+
+/**
+ * Classifies a file into a broad category (e.g. for choosing a file tree icon),
+ * based on its sniffed MIME type and extension.
+ *
+ * @param filePath - Path to the file
+ * @returns Promise resolving to the file's category
+ */
+export async function getFileTypeCategory(filePath: string): Promise<FileTypeCategory> {
+	const extension = path.extname(filePath).toLowerCase()
+	if (DOCUMENT_EXTENSIONS.has(extension)) {
+		return "document"
+	}
+
+	const mime = await detectMimeType(filePath)
+	const [type] = mime.split("/")
+
+	if (type === "image" || type === "video" || type === "audio") {
+		return type
+	}
+	if (ARCHIVE_MIME_TYPES.has(mime)) {
+		return "archive"
+	}
+	if (mime === "application/pdf") {
+		return "document"
+	}
+
+	const isTextual = type === "text" || mime === "application/json" || mime === "application/xml"
+	if (!isTextual) {
+		return "binary"
+	}
+	return CODE_EXTENSIONS.has(extension) ? "code" : "text"
+}
+
+/**
+ * Gets the interpreter named by a script's shebang line, e.g. "bash" for
+ * `#!/bin/bash` or "python3" for `#!/usr/bin/env python3`. Options and
+ * environment assignments passed to env (`env -S`, `env FOO=1 node`) are skipped.
+ *
+ * @param filePath - Path to the script
+ * @returns Promise resolving to the interpreter's basename, or null if the file has no shebang
+ */
+export async function getShebangInterpreter(filePath: string): Promise<string | null> {
+	const header = (await readMagicBytes(filePath, SNIFF_LENGTH)).toString("utf8")
+	if (!header.startsWith("#!")) {
+		return null
+	}
+
+	const [command, ...args] = header.slice(2).split("\n")[0].trim().split(/\s+/)
+	if (!command) {
+		return null
+	}
+
+	if (path.posix.basename(command) !== "env") {
+		return path.posix.basename(command)
+	}
+
+	const interpreter = args.find((arg) => !arg.startsWith("-") && !arg.includes("="))
+	return interpreter ? path.posix.basename(interpreter) : null
+}
@@ -0,0 +1,618 @@
+import { detectMimeTypeFromBuffer } from "./file-type"
+
+export interface ImageDimensions {
+	/** Detected MIME type, e.g. "image/png" */
+	format: string
+	width: number
+	height: number
+}
+
+/**
+ * JPEG start-of-frame markers that carry the image dimensions. C4 (DHT), C8
+ * (JPG extension) and CC (DAC) share the range but are not frame headers.
+ */
+const JPEG_SOF_MARKERS = new Set([0xc0, 0xc1, 0xc2, 0xc3, 0xc5, 0xc6, 0xc7, 0xc9, 0xca, 0xcb, 0xcd, 0xce, 0xcf])
+
+/**
+ * Walks JPEG segments until a start-of-frame marker and returns its offset.
+ */
+function findJpegFrame(data: Buffer): number | undefined {
+	let offset = 2
+	while (offset + 9 < data.length) {
+		if (data[offset] !== 0xff) {
+			return undefined
+		}
+
+		const marker = data[offset + 1]
+		if (marker === 0xff) {
+			// Fill byte before the marker
+			offset++
+			continue
+		}
+		if (JPEG_SOF_MARKERS.has(marker)) {
+			return offset
+		}
+		if (marker === 0xd9 || marker === 0xda) {
+			// End of image, or start of scan without a preceding frame header
+			return undefined
+		}
+		if (marker === 0x01 || (marker >= 0xd0 && marker <= 0xd8)) {
+			offset += 2
+			continue
+		}
+
+		offset += 2 + data.readUInt16BE(offset + 2)
+	}
+	return undefined
+}
+
+/**
+ * Reads WebP dimensions from the first chunk of the RIFF container.
+ */
+function readWebpDimensions(data: Buffer): { width: number; height: number } | undefined {
+	const chunk = data.toString("latin1", 12, 16)
+
+	if (chunk === "VP8 " && data.length >= 30) {
+		return { width: data.readUInt16LE(26) & 0x3fff, height: data.readUInt16LE(28) & 0x3fff }
+	}
+	if (chunk === "VP8L" && data.length >= 25) {
+		const bits = data.readUInt32LE(21)
+		return { width: (bits & 0x3fff) + 1, height: ((bits >>> 14) & 0x3fff) + 1 }
+	}
+	if (chunk === "VP8X" && data.length >= 30) {
+		return { width: data.readUIntLE(24, 3) + 1, height: data.readUIntLE(27, 3) + 1 }
+	}
+	return undefined
+}
+
+/**
+ * Reads image dimensions from the header alone, without decoding pixel data.
+ * Supports PNG, JPEG, GIF, WebP and BMP.
+ *
+ * @param data - Image bytes; only the header needs to be present
+ * @returns The format and dimensions, or undefined if the format is unsupported or the header is truncated
+ */
+export function getImageDimensions(data: Buffer): ImageDimensions | undefined {
+	const format = detectMimeTypeFromBuffer(data)
+	let size: { width: number; height: number } | undefined
+
+	switch (format) {
+		case "image/png":
+			if (data.length >= 24 && data.toString("latin1", 12, 16) === "IHDR") {
+				size = { width: data.readUInt32BE(16), height: data.readUInt32BE(20) }
+			}
+			break
+		case "image/gif":
+			if (data.length >= 10) {
+				size = { width: data.readUInt16LE(6), height: data.readUInt16LE(8) }
+			}
+			break
+		case "image/bmp":
+			if (data.length >= 26 && data.readUInt32LE(14) === 12) {
+				size = { width: data.readUInt16LE(18), height: data.readUInt16LE(20) }
+			} else if (data.length >= 26) {
+				// Negative heights mark top-down bitmaps
+				size = { width: Math.abs(data.readInt32LE(18)), height: Math.abs(data.readInt32LE(22)) }
+			}
+			break
+		case "image/webp":
+			size = readWebpDimensions(data)
+			break
+		case "image/jpeg": {
+			const frame = findJpegFrame(data)
+			if (frame !== undefined) {
+				size = { width: data.readUInt16BE(frame + 7), height: data.readUInt16BE(frame + 5) }
+			}
+			break
+		}
+	}
+
+	return format && size ? { format, ...size } : undefined
+}
+
+/**
+ * Reads the dimensions of many images from their headers, e.g. to lay out a
+ * gallery before any image is decoded.
+ *
+ * @param images - Image bytes; only each header needs to be present
+ * @returns Each image's dimensions in input order, or null where they can't be read
+ */
+export function getDimensionsBatch(images: Buffer[]): Array<{ width: number; height: number } | null> {
+	return images.map((data) => {
+		const dimensions = getImageDimensions(data)
+		return dimensions ? { width: dimensions.width, height: dimensions.height } : null
+	})
+}
+
+/**
+ * JPEG start-of-frame markers for progressive encodings (SOF2, SOF6, SOF10, SOF14)
+ */
+const JPEG_PROGRESSIVE_MARKERS = new Set([0xc2, 0xc6, 0xca, 0xce])
+
+/**
+ * Classifies a WebP as lossy, lossless or animated from its chunk list.
+ */
+function getWebpVariant(data: Buffer): string | undefined {
+	const chunk = data.toString("latin1", 12, 16)
+	if (chunk === "VP8 ") return "lossy"
+	if (chunk === "VP8L") return "lossless"
+	if (chunk !== "VP8X" || data.length <= 20) return undefined
+	if (data[20] & 0x02) return "animated"
+
+	// Extended still images carry the bitstream in a later chunk
+	let offset = 12
+	while (offset + 8 <= data.length) {
+		const type = data.toString("latin1", offset, offset + 4)
+		if (type === "VP8 ") return "lossy"
+		if (type === "VP8L") return "lossless"
+		const size = data.readUInt32LE(offset + 4)
+		offset += 8 + size + (size % 2)
+	}
+	return undefined
+}
+
+/**
+ * Detects an image's format along with the encoding variant that matters to
+ * some decoders: "baseline" or "progressive" for JPEG, "interlaced" for Adam7
+ * PNGs, and "lossy", "lossless" or "animated" for WebP. The variant is omitted
+ * for other formats and when the header doesn't reveal it.
+ *
+ * @param data - Image bytes; only the header needs to be present
+ * @returns The MIME type and, when known, the variant
+ * @throws Error if the data is not a supported image format
+ */
+export function getFormatDetailed(data: Buffer): { format: string; variant?: string } {
+	const format = detectMimeTypeFromBuffer(data)
+	let variant: string | undefined
+
+	switch (format) {
+		case "image/jpeg": {
+			const frame = findJpegFrame(data)
+			if (frame !== undefined) {
+				variant = JPEG_PROGRESSIVE_MARKERS.has(data[frame + 1]) ? "progressive" : "baseline"
+			}
+			break
+		}
+		case "image/png":
+			if (data.length > 28 && data[28] === 1) {
+				variant = "interlaced"
+			}
+			break
+		case "image/webp":
+			variant = getWebpVariant(data)
+			break
+		case "image/gif":
+		case "image/bmp":
+			break
+		default:
+			throw new Error("Unsupported image format")
+	}
+
+	return variant ? { format, variant } : { format }
+}
+
+/**
+ * Common aspect ratios that {@link getAspectRatio} snaps to
+ */
+const COMMON_ASPECT_RATIOS: Array<[string, number]> = [
+	["1:1", 1],
+	["5:4", 5 / 4],
+	["4:3", 4 / 3],
+	["3:2", 3 / 2],
+	["16:10", 16 / 10],
+	["16:9", 16 / 9],
+	["21:9", 21 / 9],
+	["4:5", 4 / 5],
+	["3:4", 3 / 4],
+	["2:3", 2 / 3],
+	["9:16", 9 / 16],
+]
+
+/**
+ * Relative difference within which a ratio is considered a match
+ */
+const ASPECT_RATIO_TOLERANCE = 0.01
+
+/**
+ * Computes an image's width/height ratio and the closest common ratio.
+ *
+ * @param data - Image bytes; only the header needs to be present
+ * @returns The ratio and the matching common ratio (e.g. "16:9"), or "other" if none is within tolerance
+ * @throws Error if the dimensions can't be read or are zero
+ */
+export function getAspectRatio(data: Buffer): { ratio: number; closest: string } {
+	const dimensions = getImageDimensions(data)
+	if (!dimensions || dimensions.width === 0 || dimensions.height === 0) {
+		throw new Error("Unable to read image dimensions")
+	}
+
+	const ratio = dimensions.width / dimensions.height
+	let closest = "other"
+	let bestDelta = ASPECT_RATIO_TOLERANCE
+
+	for (const [name, value] of COMMON_ASPECT_RATIOS) {
+		const delta = Math.abs(ratio - value) / value
+		if (delta <= bestDelta) {
+			closest = name
+			bestDelta = delta
+		}
+	}
+
+	return { ratio, closest }
+}
+
+/**
+ * Reads GIF frame delays from Graphic Control Extensions, one per image descriptor.
+ */
+function readGifFrameDelays(data: Buffer): number[] {
+	const delays: number[] = []
+	let pendingDelay = 0
+
+	const skipSubBlocks = (offset: number) => {
+		while (offset < data.length && data[offset] !== 0) {
+			offset += data[offset] + 1
+		}
+		return offset + 1
+	}
+
+	// Header plus logical screen descriptor, then the optional global color table
+	let offset = 13
+	if (data[10] & 0x80) {
+		offset += 3 * 2 ** ((data[10] & 0x07) + 1)
+	}
+
+	while (offset < data.length) {
+		const block = data[offset]
+		if (block === 0x21) {
+			if (data[offset + 1] === 0xf9 && offset + 6 <= data.length) {
+				// Delay is stored in hundredths of a second
+				pendingDelay = data.readUInt16LE(offset + 4) * 10
+			}
+			offset = skipSubBlocks(offset + 2)
+		} else if (block === 0x2c) {
+			delays.push(pendingDelay)
+			pendingDelay = 0
+
+			const packed = data[offset + 9]
+			offset += 10
+			if (packed & 0x80) {
+				offset += 3 * 2 ** ((packed & 0x07) + 1)
+			}
+			// Skip the LZW minimum code size byte, then the image data
+			offset = skipSubBlocks(offset + 1)
+		} else {
+			// Trailer or corrupt data
+			break
+		}
+	}
+
+	return delays
+}
+
+/**
+ * Reads APNG frame delays from fcTL chunks. Plain PNGs have no fcTL chunks.
+ */
+function readPngFrameDelays(data: Buffer): number[] {
+	const delays: number[] = []
+	let offset = 8
+
+	while (offset + 8 <= data.length) {
+		const length = data.readUInt32BE(offset)
+		const type = data.toString("latin1", offset + 4, offset + 8)
+
+		if (type === "fcTL" && offset + 8 + 24 <= data.length) {
+			const numerator = data.readUInt16BE(offset + 8 + 20)
+			// A zero denominator means hundredths of a second
+			const denominator = data.readUInt16BE(offset + 8 + 22) || 100
+			delays.push(Math.round((numerator / denominator) * 1000))
+		} else if (type === "IEND") {
+			break
+		}
+
+		offset += 12 + length
+	}
+
+	return delays
+}
+
+/**
+ * Reads animated WebP frame durations from ANMF chunks. Still WebPs have none.
+ */
+function readWebpFrameDelays(data: Buffer): number[] {
+	const delays: number[] = []
+	let offset = 12
+
+	while (offset + 8 <= data.length) {
+		const type = data.toString("latin1", offset, offset + 4)
+		const size = data.readUInt32LE(offset + 4)
+
+		if (type === "ANMF" && offset + 8 + 15 <= data.length) {
+			delays.push(data.readUIntLE(offset + 8 + 12, 3))
+		}
+
+		// Chunks are padded to an even size
+		offset += 8 + size + (size % 2)
+	}
+
+	return delays
+}
+
+/**
+ * Returns the delay of each frame of an animated GIF, APNG or WebP, parsed from
+ * frame metadata without decoding pixels. Static images (including JPEG and BMP)
+ * report a single frame with no delay.
+ *
+ * @param data - Image bytes
+ * @returns Delay of each frame in milliseconds, in playback order
+ * @throws Error if the data is not a supported image format
+ */
+export function getFrameDelays(data: Buffer): number[] {
+	let delays: number[]
+
+	switch (detectMimeTypeFromBuffer(data)) {
+		case "image/gif":
+			delays = readGifFrameDelays(data)
+			break
+		case "image/png":
+			delays = readPngFrameDelays(data)
+			break
+		case "image/webp":
+			delays = readWebpFrameDelays(data)
+			break
+		case "image/jpeg":
+		case "image/bmp":
+			delays = []
+			break
+		default:
+			throw new Error("Unsupported image format")
+	}
+
+	return delays.length > 0 ? delays : [0]
+}
+
+/**
+ * Checks whether a PNG declares a tRNS (transparency) chunk before its image data.
+ */
+function pngHasTransparencyChunk(data: Buffer): boolean {
+	let offset = 8
+	while (offset + 8 <= data.length) {
+		const type = data.toString("latin1", offset + 4, offset + 8)
+		if (type === "tRNS") return true
+		if (type === "IDAT" || type === "IEND") return false
+		offset += 12 + data.readUInt32BE(offset)
+	}
+	return false
+}
+
+/**
+ * Maps a PNG header to the color type of its decoded pixels. Low bit-depth
+ * grayscale and palette images decode to 8 bits per channel, and a tRNS chunk
+ * adds an alpha channel.
+ */
+function getPngColorType(data: Buffer): string | undefined {
+	if (data.length < 26 || data.toString("latin1", 12, 16) !== "IHDR") {
+		return undefined
+	}
+
+	const depth = data[24] === 16 ? 16 : 8
+	const hasTransparency = pngHasTransparencyChunk(data)
+
+	switch (data[25]) {
+		case 0:
+			return hasTransparency ? `la${depth}` : `l${depth}`
+		case 2:
+			return hasTransparency ? `rgba${depth}` : `rgb${depth}`
+		case 3:
+			return hasTransparency ? "rgba8" : "rgb8"
+		case 4:
+			return `la${depth}`
+		case 6:
+			return `rgba${depth}`
+		default:
+			return undefined
+	}
+}
+
+/**
+ * Returns the color type an image decodes to, as a stable string such as
+ * "l8", "la8", "rgb8", "rgba8", "l16", "la16", "rgb16" or "rgba16". This tells
+ * callers whether a conversion will drop alpha or precision. Only the header is
+ * parsed.
+ *
+ * @param data - Image bytes; only the header needs to be present
+ * @returns The color type of the decoded pixels
+ * @throws Error if the format is unsupported or the header is unreadable
+ */
+export function getColorType(data: Buffer): string {
+	let colorType: string | undefined
+
+	switch (detectMimeTypeFromBuffer(data)) {
+		case "image/png":
+			colorType = getPngColorType(data)
+			break
+		case "image/jpeg": {
+			const frame = findJpegFrame(data)
+			if (frame !== undefined) {
+				// CMYK (4 components) is converted to RGB when decoded
+				colorType = data[frame + 9] === 1 ? "l8" : "rgb8"
+			}
+			break
+		}
+		case "image/gif":
+			// GIF frames decode to RGBA since any palette entry may be transparent
+			colorType = "rgba8"
+			break
+		case "image/webp": {
+			const chunk = data.toString("latin1", 12, 16)
+			if (chunk === "VP8X" && data.length > 20) {
+				colorType = data[20] & 0x10 ? "rgba8" : "rgb8"
+			} else if (chunk === "VP8L" && data.length >= 25) {
+				colorType = data.readUInt32LE(21) & (1 << 28) ? "rgba8" : "rgb8"
+			} else if (chunk === "VP8 ") {
+				colorType = "rgb8"
+			}
+			break
+		}
+		case "image/bmp":
+			if (data.length >= 30) {
+				colorType = data.readUInt16LE(28) === 32 ? "rgba8" : "rgb8"
+			}
+			break
+	}
+
+	if (!colorType) {
+		throw new Error("Unsupported image format")
+	}
+	return colorType
+}
+
+/**
+ * Locates the TIFF-structured EXIF block of a JPEG (APP1 "Exif" segment) or PNG
+ * (eXIf chunk).
+ */
+function findExifData(data: Buffer): Buffer | undefined {
+	switch (detectMimeTypeFromBuffer(data)) {
+		case "image/jpeg": {
+			let offset = 2
+			while (offset + 4 <= data.length && data[offset] === 0xff) {
+				const marker = data[offset + 1]
+				if (marker === 0xda || marker === 0xd9) {
+					break
+				}
+				const length = data.readUInt16BE(offset + 2)
+				if (marker === 0xe1 && data.toString("latin1", offset + 4, offset + 10) === "Exif\0\0") {
+					return data.subarray(offset + 10, offset + 2 + length)
+				}
+				offset += 2 + length
+			}
+			return undefined
+		}
+		case "image/png": {
+			let offset = 8
+			while (offset + 8 <= data.length) {
+				const length = data.readUInt32BE(offset)
+				const type = data.toString("latin1", offset + 4, offset + 8)
+				if (type === "eXIf") {
+					return data.subarray(offset + 8, offset + 8 + length)
+				}
+				if (type === "IDAT" || type === "IEND") {
+					return undefined
+				}
+				offset += 12 + length
+			}
+			return undefined
+		}
+		default:
+			return undefined
+	}
+}
+
+/**
+ * Reads the value of an ASCII, SHORT or LONG tag from an EXIF IFD.
+ */
+function readExifTag(tiff: Buffer, ifdOffset: number, tag: number): string | number | undefined {
+	const littleEndian = tiff.toString("latin1", 0, 2) === "II"
+	const readUInt16 = (offset: number) => (littleEndian ? tiff.readUInt16LE(offset) : tiff.readUInt16BE(offset))
+	const readUInt32 = (offset: number) => (littleEndian ? tiff.readUInt32LE(offset) : tiff.readUInt32BE(offset))
+
+	if (ifdOffset + 2 > tiff.length) {
+		return undefined
+	}
+
+	const entryCount = readUInt16(ifdOffset)
+	for (let index = 0; index < entryCount; index++) {
+		const entry = ifdOffset + 2 + index * 12
+		if (entry + 12 > tiff.length) {
+			return undefined
+		}
+		if (readUInt16(entry) !== tag) {
+			continue
+		}
+
+		const type = readUInt16(entry + 2)
+		const count = readUInt32(entry + 4)
+		if (type === 3) {
+			return readUInt16(entry + 8)
+		}
+		if (type === 4) {
+			return readUInt32(entry + 8)
+		}
+		if (type === 2) {
+			// Values longer than four bytes are stored at an offset
+			const start = count > 4 ? readUInt32(entry + 8) : entry + 8
+			return tiff.toString("latin1", start, Math.min(start + count, tiff.length)).replace(/\0.*$/s, "")
+		}
+		return undefined
+	}
+	return undefined
+}
+
+const EXIF_ORIENTATION_TAG = 0x0112
+const EXIF_IFD_POINTER_TAG = 0x8769
+const EXIF_DATE_TIME_ORIGINAL_TAG = 0x9003
+const EXIF_DATE_TIME_TAG = 0x0132
+
+/**
+ * Finds the TIFF data of an image's EXIF block along with the offset of its first IFD.
+ */
+function readExifIfd0(data: Buffer): { tiff: Buffer; ifd0: number } | undefined {
+	const tiff = findExifData(data)
+	if (!tiff || tiff.length < 8 || !["II", "MM"].includes(tiff.toString("latin1", 0, 2))) {
+		return undefined
+	}
+	return { tiff, ifd0: tiff.toString("latin1", 0, 2) === "II" ? tiff.readUInt32LE(4) : tiff.readUInt32BE(4) }
+}
+
+/**
+ * Returns the raw EXIF orientation value without applying it, so callers can
+ * decide whether to rotate or flip the image themselves. 1 is upright, 6 needs
+ * a 90° clockwise rotation and 8 a 90° counter-clockwise rotation.
+ *
+ * @param data - Image bytes (JPEG or PNG)
+ * @returns The orientation from 1 to 8, or 1 when the image has no valid orientation tag
+ */
+export function getOrientationTag(data: Buffer): number {
+	const exif = readExifIfd0(data)
+	const orientation = exif && readExifTag(exif.tiff, exif.ifd0, EXIF_ORIENTATION_TAG)
+	return typeof orientation === "number" && orientation >= 1 && orientation <= 8 ? orientation : 1
+}
+
+/**
+ * Parses an EXIF "YYYY:MM:DD HH:MM:SS" date as UTC.
+ */
+function parseExifDate(value: string | number | undefined): number | null {
+	const match = typeof value === "string" && value.match(/^(\d{4}):(\d{2}):(\d{2}) (\d{2}):(\d{2}):(\d{2})/)
+	if (!match) {
+		return null
+	}
+
+	const [year, month, day, hour, minute, second] = match.slice(1).map(Number)
+	if (month < 1 || day < 1) {
+		// Cameras write "0000:00:00 00:00:00" when the clock was never set
+		return null
+	}
+	return Date.UTC(year, month - 1, day, hour, minute, second)
+}
+
+/**
+ * Returns the capture time of a photo as a sortable timestamp, taken from the
+ * EXIF DateTimeOriginal tag, falling back to DateTime. EXIF dates carry no
+ * timezone, so they are always interpreted as UTC; the result is consistent
+ * across machines but may be offset from the true instant by the camera's
+ * timezone. Supports EXIF in JPEG and PNG files.
+ *
+ * @param data - Image bytes
+ * @returns Epoch milliseconds, or null if the image has no parseable capture time
+ */
+export function getCaptureTimestamp(data: Buffer): number | null {
+	const exif = readExifIfd0(data)
+	if (!exif) {
+		return null
+	}
+
+	const { tiff, ifd0 } = exif
+	const exifIfd = readExifTag(tiff, ifd0, EXIF_IFD_POINTER_TAG)
+	const original = typeof exifIfd === "number" ? readExifTag(tiff, exifIfd, EXIF_DATE_TIME_ORIGINAL_TAG) : undefined
+
+	return parseExifDate(original) ?? parseExifDate(readExifTag(tiff, ifd0, EXIF_DATE_TIME_TAG))
+}
@@ -13,6 +13,8 @@
  * 6. Apply line limit
  */
 
+import fs from "fs/promises"
+
 import {
 	DEFAULT_LINE_LIMIT,
 	DEFAULT_MAX_LEVELS,
@@ -49,6 +51,15 @@ export interface IndentationReadOptions {
 	maxLines?: number
 }
 
+export interface OutlineEntry {
+	/** 1-based line number */
+	line: number
+	/** Indentation depth in units of the detected indent size */
+	depth: number
+	/** Line content without surrounding whitespace */
+	text: string
+}
+
 export interface IndentationReadResult {
 	/** The extracted content with line numbers */
 	content: string
@@ -467,3 +478,111 @@ export function readWithSlice(
 		wasTruncated,
 	}
 }
+
+// ─── Outline ──────────────────────────────────────────────────────────────────
+
+/** Markdown-style headings count as outline entries regardless of indentation */
+const MARKDOWN_HEADING_PATTERN = /^#{1,6}\s/
+
+/**
+ * Measure leading whitespace in columns (tabs = TAB_WIDTH columns).
+ */
+function measureIndentColumns(line: string): number {
+	let columns = 0
+	for (const char of line) {
+		if (char === "\t") {
+			columns += TAB_WIDTH
+		} else if (char === " ") {
+			columns += 1
+		} else {
+			break
+		}
+	}
+	return columns
+}
+
+/**
+ * Detect the indent size of a file as the most common positive increase in
+ * indentation between consecutive non-blank lines. Falls back to INDENT_SIZE.
+ */
+export function detectIndentSize(content: string): number {
+	const deltas = new Map<number, number>()
+	let previous = 0
+
+	for (const line of content.split("\n")) {
+		if (line.trim().length === 0) continue
+
+		const columns = measureIndentColumns(line)
+		if (columns > previous) {
+			const delta = columns - previous
+			deltas.set(delta, (deltas.get(delta) ?? 0) + 1)
+		}
+		previous = columns
+	}
+
+	let best = INDENT_SIZE
+	let bestCount = 0
+	for (const [delta, count] of deltas) {
+		if (count > bestCount || (count === bestCount && delta < best)) {
+			best = delta
+			bestCount = count
+		}
+	}
+	return best
+}
+
+/**
+ * Compute a lightweight structural outline of a file without tree-sitter.
+ *
+ * A non-blank line is part of the outline when its indentation decreases relative
+ * to the previous non-blank line, when it opens a deeper block (the next non-blank
+ * line is indented further), or when it looks like a header (block start or
+ * markdown heading). Depths are leading whitespace normalized by the detected indent size.
+ *
+ * @param content - The file content to process
+ * @param maxEntries - Optional. Maximum number of entries to return
+ * @returns Outline entries in file order
+ */
+export function computeIndentationOutline(content: string, maxEntries?: number): OutlineEntry[] {
+	const indentSize = detectIndentSize(content)
+	const lines = content.split("\n")
+	const nonBlank = lines
+		.map((line, index) => ({ index, line, columns: measureIndentColumns(line) }))
+		.filter(({ line }) => line.trim().length > 0)
+
+	const outline: OutlineEntry[] = []
+	let previousColumns = 0
+
+	for (let k = 0; k < nonBlank.length; k++) {
+		if (maxEntries !== undefined && outline.length >= maxEntries) break
+
+		const { index, line, columns } = nonBlank[k]
+		const text = line.trim()
+		const nextColumns = k + 1 < nonBlank.length ? nonBlank[k + 1].columns : columns
+
+		const decreases = columns < previousColumns
+		const opensBlock = nextColumns > columns
+		const looksLikeHeader =
+			MARKDOWN_HEADING_PATTERN.test(text) || BLOCK_START_PATTERNS.some((pattern) => pattern.test(line))
+
+		if (decreases || opensBlock || looksLikeHeader) {
+			outline.push({ line: index + 1, depth: Math.floor(columns / indentSize), text })
+		}
+
+		previousColumns = columns
+	}
+
+	return outline
+}
+
+/**
+ * Read a file and compute its indentation outline.
+ *
+ * @param filePath - Path to the file to outline
+ * @param maxEntries - Optional. Maximum number of entries to return
+ * @returns Promise resolving to the outline entries
+ */
+export async function getIndentationOutline(filePath: string, maxEntries?: number): Promise<OutlineEntry[]> {
+	const content = await fs.readFile(filePath, "utf8")
+	return computeIndentationOutline(content, maxEntries)
+}
@@ -1,15 +1,69 @@
 import fs, { createReadStream } from "fs"
 import { createInterface } from "readline"
+import pLimit from "p-limit"
 import { countTokens } from "../../utils/countTokens"
 import { Anthropic } from "@anthropic-ai/sdk"
 
+import { getBatchConcurrency } from "./batch-concurrency"
+
+export interface CountLinesOptions {
+	/**
+	 * Single-byte separators that end a line, e.g. ["\n", "\f"] to also count
+	 * form-feed page breaks. By default lines end at LF, CRLF or CR.
+	 */
+	separators?: string[]
+}
+
+/**
+ * Converts separator strings to the byte values they stand for.
+ */
+function separatorBytes(separators: string[]): Set<number> {
+	return new Set(
+		separators.map((separator) => {
+			const bytes = Buffer.from(separator, "utf8")
+			if (bytes.length !== 1) {
+				throw new RangeError(`Invalid separator: ${JSON.stringify(separator)}. Separators must be one byte.`)
+			}
+			return bytes[0]
+		}),
+	)
+}
+
+/**
+ * Counts lines in a buffer, where a line ends at any of the given separators,
+ * or by default at LF, CRLF or CR like countFileLines. A final line without a
+ * trailing separator is counted too.
+ *
+ * @param data - The content to count
+ * @param options - Which separators end a line (default: LF, CRLF or CR)
+ * @returns The number of lines
+ */
+export function countLinesInBuffer(data: Buffer, options: CountLinesOptions = {}): number {
+	if (!options.separators) {
+		let count = 0
+		for (let i = 0; i < data.length; i++) {
+			if (data[i] === 0x0a || (data[i] === 0x0d && data[i + 1] !== 0x0a)) count++
+		}
+		const lastByte = data[data.length - 1]
+		return count + (data.length > 0 && lastByte !== 0x0a && lastByte !== 0x0d ? 1 : 0)
+	}
+
+	const separators = separatorBytes(options.separators)
+	let count = 0
+	for (const byte of data) {
+		if (separators.has(byte)) count++
+	}
+	return count + (data.length > 0 && !separators.has(data[data.length - 1]) ? 1 : 0)
+}
+
 /**
  * Efficiently counts lines in a file using streams without loading the entire file into memory
  *
  * @param filePath - Path to the file to count lines in
+ * @param options - Optional separators to count instead of line breaks
  * @returns A promise that resolves to the number of lines in the file
  */
-export async function countFileLines(filePath: string): Promise<number> {
+export async function countFileLines(filePath: string, options: CountLinesOptions = {}): Promise<number> {
 	// Check if file exists
 	try {
 		await fs.promises.access(filePath, fs.constants.F_OK)
@@ -17,6 +71,20 @@ export async function countFileLines(filePath: string): Promise<number> {
 		throw new Error(`File not found: ${filePath}`)
 	}
 
+	if (options.separators) {
+		const separators = separatorBytes(options.separators)
+		let count = 0
+		let lastByte: number | undefined
+		for await (const chunk of createReadStream(filePath)) {
+			const bytes = Buffer.isBuffer(chunk) ? chunk : Buffer.from(chunk)
+			for (const byte of bytes) {
+				if (separators.has(byte)) count++
+			}
+			lastByte = bytes.length > 0 ? bytes[bytes.length - 1] : lastByte
+		}
+		return count + (lastByte !== undefined && !separators.has(lastByte) ? 1 : 0)
+	}
+
 	return new Promise((resolve, reject) => {
 		let lineCount = 0
 
@@ -44,6 +112,17 @@ export async function countFileLines(filePath: string): Promise<number> {
 	})
 }
 
+/**
+ * Counts lines in many files concurrently (see setBatchConcurrency).
+ *
+ * @param filePaths - Paths of the files to count
+ * @returns A promise that resolves to each file's line count in input order (-1 for unreadable files)
+ */
+export async function countFileLinesBatch(filePaths: string[]): Promise<number[]> {
+	const limit = pLimit(getBatchConcurrency())
+	return Promise.all(filePaths.map((filePath) => limit(() => countFileLines(filePath).catch(() => -1))))
+}
+
 export interface LineAndTokenCountResult {
 	/** Total number of lines counted */
 	lineCount: number
@@ -0,0 +1,179 @@
+import fs from "fs/promises"
+import { diffArrays } from "diff"
+
+import { writeFileAtomic } from "../../utils/fs"
+
+interface ParsedLines {
+	lines: string[]
+	/** Line ending used when writing the file back */
+	eol: "\r\n" | "\n"
+	/** Whether the last line ends with a line ending */
+	trailingNewline: boolean
+}
+
+/**
+ * Splits file content into lines, remembering its line ending style and whether
+ * it ends with a newline so it can be written back the same way.
+ */
+function parseLines(content: string): ParsedLines {
+	const eol = content.includes("\r\n") ? "\r\n" : "\n"
+	if (content === "") {
+		return { lines: [], eol, trailingNewline: false }
+	}
+
+	const lines = content.split(/\r?\n/)
+	const trailingNewline = lines[lines.length - 1] === ""
+	if (trailingNewline) {
+		lines.pop()
+	}
+	return { lines, eol, trailingNewline }
+}
+
+/**
+ * Splits inserted content into lines. A single trailing newline does not add an
+ * extra empty line, and empty content contributes no lines.
+ */
+function contentToLines(content: string): string[] {
+	return content === "" ? [] : content.replace(/\r?\n$/, "").split(/\r?\n/)
+}
+
+/**
+ * Applies an edit to a file's lines and writes the result back atomically,
+ * keeping its line ending style and trailing newline.
+ *
+ * @returns Promise resolving to the new total line count
+ */
+async function editLines(filePath: string, edit: (lines: string[]) => void): Promise<number> {
+	const { lines, eol, trailingNewline } = parseLines(await fs.readFile(filePath, "utf8"))
+
+	edit(lines)
+
+	const content = lines.join(eol) + (trailingNewline && lines.length > 0 ? eol : "")
+	await writeFileAtomic(filePath, content)
+	return lines.length
+}
+
+function validateLineRange(startLine: number, endLine: number, lineCount: number): void {
+	if (!Number.isInteger(startLine) || !Number.isInteger(endLine) || startLine < 1 || endLine < startLine) {
+		throw new RangeError(
+			`Invalid line range ${startLine}-${endLine}. Lines are 1-based and startLine must not exceed endLine.`,
+		)
+	}
+	if (endLine > lineCount) {
+		throw new RangeError(`Line range ${startLine}-${endLine} is outside the file's ${lineCount} lines`)
+	}
+}
+
+/**
+ * Replaces an inclusive range of lines with new content, which may span any
+ * number of lines (including none). The file's line endings are preserved.
+ *
+ * @param filePath - Path to the file to edit
+ * @param startLine - 1-based first line to replace
+ * @param endLine - 1-based last line to replace (inclusive)
+ * @param newContent - Replacement text
+ * @returns Promise resolving to the new total line count
+ * @throws {RangeError} If the range is invalid or outside the file
+ */
+export async function replaceLineRange(
+	filePath: string,
+	startLine: number,
+	endLine: number,
+	newContent: string,
+): Promise<number> {
+	return editLines(filePath, (lines) => {
+		validateLineRange(startLine, endLine, lines.length)
+		lines.splice(startLine - 1, endLine - startLine + 1, ...contentToLines(newContent))
+	})
+}
+
+/**
+ * Inserts content before a 1-based line. Line 1 prepends, and any line past the
+ * end of the file appends. The file's line endings are preserved.
+ *
+ * @param filePath - Path to the file to edit
+ * @param line - 1-based line to insert before
+ * @param content - Text to insert, which may span several lines
+ * @returns Promise resolving to the new total line count
+ * @throws {RangeError} If line is not a positive integer
+ */
+export async function insertAtLine(filePath: string, line: number, content: string): Promise<number> {
+	if (!Number.isInteger(line) || line < 1) {
+		throw new RangeError(`Invalid line: ${line}. Lines are 1-based.`)
+	}
+
+	return editLines(filePath, (lines) => {
+		lines.splice(Math.min(line - 1, lines.length), 0, ...contentToLines(content))
+	})
+}
+
+/**
+ * Deletes an inclusive range of lines. Deleting every line leaves an empty file;
+ * otherwise the file's line endings and trailing newline are preserved.
+ *
+ * @param filePath - Path to the file to edit
+ * @param startLine - 1-based first line to delete
+ * @param endLine - 1-based last line to delete (inclusive)
+ * @returns Promise resolving to the new total line count
+ * @throws {RangeError} If the range is invalid or outside the file
+ */
+export async function deleteLineRange(filePath: string, startLine: number, endLine: number): Promise<number> {
+	return replaceLineRange(filePath, startLine, endLine, "")
+}
+
+export interface ChangedLine {
+	/** 1-based line number in the current file */
+	line: number
+	/**
+	 * "modified" and "added" lines exist in the current file. "deleted" marks
+	 * where lines were removed: the line that now follows them, or the line count
+	 * plus one for lines removed from the end.
+	 */
+	type: "added" | "modified" | "deleted"
+}
+
+/**
+ * Compares a file with an earlier snapshot of its content and reports which of
+ * its current lines changed, so only those regions need to be reprocessed.
+ * Line ending style and the trailing newline are ignored.
+ *
+ * @param filePath - Path to the current file
+ * @param previousContent - The file's content at the earlier snapshot
+ * @returns Promise resolving to the changed lines in file order
+ */
+export async function changedLines(filePath: string, previousContent: Buffer): Promise<ChangedLine[]> {
+	const previous = parseLines(previousContent.toString("utf8")).lines
+	const current = parseLines(await fs.readFile(filePath, "utf8")).lines
+
+	const changes: ChangedLine[] = []
+	let line = 1
+	let pendingRemoved = 0
+
+	for (const part of diffArrays(previous, current)) {
+		const count = part.value.length
+		if (part.removed) {
+			pendingRemoved += count
+			continue
+		}
+
+		if (part.added) {
+			// Added lines replacing removed ones are modifications
+			for (let index = 0; index < count; index++) {
+				changes.push({ line: line + index, type: index < pendingRemoved ? "modified" : "added" })
+			}
+			if (pendingRemoved > count) {
+				changes.push({ line: line + count, type: "deleted" })
+			}
+		} else if (pendingRemoved > 0) {
+			changes.push({ line, type: "deleted" })
+		}
+
+		pendingRemoved = 0
+		line += count
+	}
+
+	if (pendingRemoved > 0) {
+		changes.push({ line, type: "deleted" })
+	}
+	return changes
+}
@@ -7,6 +7,9 @@
  * Now you can read a range of lines from a file
  */
 import { createReadStream } from "fs"
+import pLimit from "p-limit"
+
+import { getBatchConcurrency } from "./batch-concurrency"
 
 const outOfRangeError = (filepath: string, n: number) => {
 	return new RangeError(`Line with index ${n} does not exist in '${filepath}'. Note that line indexing is zero-based`)
@@ -18,7 +21,8 @@ const outOfRangeError = (filepath: string, n: number) => {
  * @param filepath - Path to the file to read
  * @param endLine - Optional. The line number to stop reading at (inclusive). If undefined, reads to the end of file.
  * @param startLine - Optional. The line number to start reading from (inclusive). If undefined, starts from line 0.
- * @returns Promise resolving to a string containing the read lines joined with newlines
+ * @returns Promise resolving to the exact text of the requested lines, including their original
+ *          line terminators (CRLF is not normalized to LF)
  * @throws {RangeError} If line numbers are invalid or out of range
  */
 export function readLines(filepath: string, endLine?: number, startLine?: number): Promise<string> {
@@ -52,8 +56,8 @@ export function readLines(filepath: string, endLine?: number, startLine?: number
 			)
 		}
 
-		// Set up stream
-		const input = createReadStream(filepath)
+		// Set up stream. Decoding in the stream keeps multi-byte characters intact across chunk boundaries.
+		const input = createReadStream(filepath, { encoding: "utf8" })
 		let buffer = ""
 		let lineCount = 0
 		let result = ""
@@ -114,3 +118,233 @@ export function readLines(filepath: string, endLine?: number, startLine?: number
 		})
 	})
 }
+
+/**
+ * A single line-range read for readLineRangesBatch.
+ */
+export interface LineRangeRequest {
+	/** Path to the file to read */
+	path: string
+	/** Zero-based first line to read (inclusive) */
+	start: number
+	/** Zero-based last line to read (inclusive) */
+	end: number
+}
+
+/**
+ * Marker returned in place of content for a request that failed.
+ */
+export interface LineRangeError {
+	error: string
+}
+
+/**
+ * Reads line ranges from several files. Each request is read with readLines and
+ * results are returned in request order. A failing request (missing file, invalid
+ * or out-of-range lines) yields a LineRangeError instead of rejecting the batch.
+ *
+ * @param requests - The ranges to read
+ * @returns Promise resolving to the content or error marker for each request
+ */
+export async function readLineRangesBatch(requests: LineRangeRequest[]): Promise<Array<string | LineRangeError>> {
+	const limit = pLimit(getBatchConcurrency())
+	const results = await Promise.allSettled(
+		requests.map(({ path, start, end }) => limit(() => readLines(path, end, start))),
+	)
+
+	return results.map((result) =>
+		result.status === "fulfilled"
+			? result.value
+			: { error: result.reason instanceof Error ? result.reason.message : String(result.reason) },
+	)
+}
+
+/**
+ * Result of readLinesWithLimit.
+ */
+export interface LimitedReadResult {
+	/** The first lines of the file, including their original line terminators */
+	content: string
+	/** Number of lines in `content` */
+	lineCount: number
+	/** Whether the file continues past `content` */
+	truncated: boolean
+}
+
+/**
+ * Reads at most `maxLines` lines from the start of a file, stopping the stream as
+ * soon as the limit is reached. Useful for files with millions of short lines,
+ * where reading the whole file just to show its head would be wasteful.
+ *
+ * @param filepath - Path to the file to read
+ * @param maxLines - Maximum number of lines to return
+ * @returns Promise resolving to the leading content and whether it was truncated
+ * @throws {RangeError} If maxLines is negative or not an integer
+ */
+export async function readLinesWithLimit(filepath: string, maxLines: number): Promise<LimitedReadResult> {
+	if (!Number.isInteger(maxLines) || maxLines < 0) {
+		throw new RangeError(`Invalid maxLines: ${maxLines}. maxLines must be a non-negative integer.`)
+	}
+
+	const collected: Buffer[] = []
+	let lineCount = 0
+	let truncated = false
+
+	for await (const chunk of createReadStream(filepath) as AsyncIterable<Buffer>) {
+		// The limit was reached exactly at the end of the previous chunk
+		if (lineCount >= maxLines) {
+			truncated = true
+			break
+		}
+
+		let offset = 0
+		while (lineCount < maxLines) {
+			const newline = chunk.indexOf(0x0a, offset)
+			if (newline === -1) break
+			lineCount++
+			offset = newline + 1
+		}
+
+		if (lineCount < maxLines) {
+			collected.push(chunk)
+			continue
+		}
+
+		collected.push(chunk.subarray(0, offset))
+		if (offset < chunk.length) {
+			truncated = true
+			break
+		}
+	}
+
+	const content = Buffer.concat(collected).toString("utf8")
+
+	// A final line without a trailing newline still counts when nothing follows it
+	if (!truncated && content.length > 0 && !content.endsWith("\n")) {
+		lineCount++
+	}
+
+	return { content, lineCount, truncated }
+}
+
+/**
+ * Reads an arbitrary selection, such as an LSP range, that may start and end
+ * partway through a line. Lines and columns are zero-based; columns count
+ * characters (code points) and the end column is exclusive.
+ *
+ * Columns past the end of a line are clamped to the line's length, and an end
+ * line past the end of the file extends the selection to the end of the file.
+ *
+ * @param filepath - Path to the file to read
+ * @param startLine - Zero-based line where the selection starts
+ * @param startCol - Zero-based character column where the selection starts (inclusive)
+ * @param endLine - Zero-based line where the selection ends
+ * @param endCol - Zero-based character column where the selection ends (exclusive)
+ * @returns Promise resolving to the selected text, including line terminators between lines
+ * @throws {RangeError} If a position is negative or not an integer, the end precedes the start, or
+ *         startLine is past the end of the file
+ */
+export async function readRange(
+	filepath: string,
+	startLine: number,
+	startCol: number,
+	endLine: number,
+	endCol: number,
+): Promise<string> {
+	for (const [name, value] of Object.entries({ startLine, startCol, endLine, endCol })) {
+		if (!Number.isInteger(value) || value < 0) {
+			throw new RangeError(`Invalid ${name}: ${value}. Positions must be non-negative integers.`)
+		}
+	}
+	if (endLine < startLine || (endLine === startLine && endCol < startCol)) {
+		throw new RangeError(`Range end (${endLine}:${endCol}) must not precede range start (${startLine}:${startCol})`)
+	}
+
+	const text = await readLines(filepath, endLine, startLine)
+	const lines = text.match(/[^\n]*\n|[^\n]+$/g) ?? [""]
+	const reachesEndLine = lines.length === endLine - startLine + 1
+
+	return lines
+		.map((line, index) => {
+			const terminator = line.match(/\r?\n$/)?.[0] ?? ""
+			const chars = Array.from(line.slice(0, line.length - terminator.length))
+			const isFirst = index === 0
+			const isLast = reachesEndLine && index === lines.length - 1
+
+			const from = isFirst ? Math.min(startCol, chars.length) : 0
+			const to = isLast ? Math.max(from, Math.min(endCol, chars.length)) : chars.length
+			return chars.slice(from, to).join("") + (isLast ? "" : terminator)
+		})
+		.join("")
+}
+
+/**
+ * Result of readLineRangeWithOffsets.
+ */
+export interface LineRangeWithOffsets {
+	/** The requested lines, including their original line terminators */
+	content: string
+	/** Byte offset in the file of the start of each returned line */
+	lineOffsets: number[]
+}
+
+/**
+ * Reads a range of lines along with the byte offset at which each line starts,
+ * so positions in the returned text can be mapped back to the file. Offsets
+ * count bytes, not characters, so they stay correct for multibyte content.
+ *
+ * @param filepath - Path to the file to read
+ * @param start - Zero-based first line to read (inclusive)
+ * @param end - Zero-based last line to read (inclusive); lines past the end of the file are ignored
+ * @returns Promise resolving to the lines and their starting byte offsets
+ * @throws {RangeError} If the range is invalid or start is past the end of the file
+ */
+export async function readLineRangeWithOffsets(
+	filepath: string,
+	start: number,
+	end: number,
+): Promise<LineRangeWithOffsets> {
+	if (!Number.isInteger(start) || !Number.isInteger(end) || start < 0 || end < start) {
+		throw new RangeError(`Invalid line range ${start}-${end}. Lines are zero-based and start must not exceed end.`)
+	}
+
+	const lineOffsets: number[] = []
+	const chunks: Buffer[] = []
+	let line = 0
+	let offset = 0
+	let atLineStart = true
+
+	// Bytes are scanned rather than decoded text so offsets stay byte-accurate
+	for await (const chunk of createReadStream(filepath)) {
+		const bytes = chunk as Buffer
+		// Portion of this chunk inside the range; a range already in progress continues from 0
+		let from = lineOffsets.length > 0 ? 0 : undefined
+		let to = bytes.length
+
+		for (let index = 0; index < bytes.length; index++) {
+			if (atLineStart && line >= start) {
+				lineOffsets.push(offset + index)
+				from ??= index
+			}
+			atLineStart = bytes[index] === 0x0a
+			if (atLineStart && ++line > end) {
+				to = index + 1
+				break
+			}
+		}
+
+		if (from !== undefined) {
+			chunks.push(bytes.subarray(from, to))
+		}
+		offset += bytes.length
+
+		if (line > end) {
+			break
+		}
+	}
+
+	if (lineOffsets.length === 0) {
+		throw outOfRangeError(filepath, start)
+	}
+	return { content: Buffer.concat(chunks).toString("utf8"), lineOffsets }
+}
@@ -0,0 +1,135 @@
+import fs from "fs/promises"
+
+import { readMagicBytes } from "./file-type"
+
+/**
+ * Decodes UTF-16 content, using the byte order mark to pick the endianness and
+ * defaulting to little-endian (what PowerShell and most Windows tools write).
+ * The BOM itself is not included in the result.
+ *
+ * @param data - Raw UTF-16 bytes
+ * @returns The decoded text
+ */
+export function decodeUtf16(data: Buffer): string {
+	let bigEndian = false
+	let body = data
+
+	if (data.length >= 2 && data[0] === 0xfe && data[1] === 0xff) {
+		bigEndian = true
+		body = data.subarray(2)
+	} else if (data.length >= 2 && data[0] === 0xff && data[1] === 0xfe) {
+		body = data.subarray(2)
+	}
+
+	// Ignore a dangling odd byte rather than failing the whole read
+	body = body.subarray(0, body.length - (body.length % 2))
+
+	if (bigEndian) {
+		body = Buffer.from(body).swap16()
+	}
+
+	return body.toString("utf16le")
+}
+
+/**
+ * Reads a UTF-16 encoded file such as a PowerShell transcript.
+ *
+ * @param filePath - Path to the file to read
+ * @returns Promise resolving to the decoded text without a BOM
+ */
+export async function readUtf16File(filePath: string): Promise<string> {
+	return decodeUtf16(await fs.readFile(filePath))
+}
+
+/**
+ * Byte order marks, longest first so UTF-32LE isn't mistaken for UTF-16LE
+ */
+const BYTE_ORDER_MARKS: Array<[string, number[]]> = [
+	["utf-32le", [0xff, 0xfe, 0x00, 0x00]],
+	["utf-32be", [0x00, 0x00, 0xfe, 0xff]],
+	["utf-8", [0xef, 0xbb, 0xbf]],
+	["utf-16le", [0xff, 0xfe]],
+	["utf-16be", [0xfe, 0xff]],
+]
+
+/**
+ * Identifies the byte order mark at the start of a file, so it can be kept
+ * when the file is written back.
+ *
+ * @param filePath - Path to the file to check
+ * @returns Promise resolving to "utf-8", "utf-16le", "utf-16be", "utf-32le" or "utf-32be", or null if there is no BOM
+ */
+export async function detectBom(filePath: string): Promise<string | null> {
+	const header = await readMagicBytes(filePath, 4)
+	const match = BYTE_ORDER_MARKS.find(
+		([, bytes]) => header.length >= bytes.length && bytes.every((byte, index) => header[index] === byte),
+	)
+	return match ? match[0] : null
+}
+
+export interface Utf8ValidationResult {
+	valid: boolean
+	/** Offset of the first byte of the first invalid or truncated sequence */
+	firstInvalidByteOffset?: number
+	/** Value of the byte at `firstInvalidByteOffset` */
+	byteValue?: number
+}
+
+/**
+ * Finds the offset of the first byte that doesn't start a well-formed UTF-8
+ * sequence, rejecting overlong encodings, surrogates and code points above
+ * U+10FFFF.
+ */
+function findInvalidUtf8(data: Buffer): number | undefined {
+	let offset = 0
+	while (offset < data.length) {
+		const lead = data[offset]
+		if (lead < 0x80) {
+			offset++
+			continue
+		}
+
+		let length: number
+		// Allowed range of the second byte, which rules out overlong and out-of-range forms
+		let [min, max] = [0x80, 0xbf]
+		if (lead >= 0xc2 && lead <= 0xdf) {
+			length = 2
+		} else if (lead >= 0xe0 && lead <= 0xef) {
+			length = 3
+			if (lead === 0xe0) min = 0xa0
+			if (lead === 0xed) max = 0x9f
+		} else if (lead >= 0xf0 && lead <= 0xf4) {
+			length = 4
+			if (lead === 0xf0) min = 0x90
+			if (lead === 0xf4) max = 0x8f
+		} else {
+			return offset
+		}
+
+		for (let index = 1; index < length; index++) {
+			const byte = data[offset + index]
+			const [low, high] = index === 1 ? [min, max] : [0x80, 0xbf]
+			if (byte === undefined || byte < low || byte > high) {
+				return offset
+			}
+		}
+		offset += length
+	}
+	return undefined
+}
+
+/**
+ * Checks whether a file is valid UTF-8 and, if not, reports where decoding
+ * fails so the problem can be located rather than just reported.
+ *
+ * @param filePath - Path to the file to validate
+ * @returns Promise resolving to the validation result, with the offending offset and byte when invalid
+ */
+export async function validateUtf8(filePath: string): Promise<Utf8ValidationResult> {
+	const data = await fs.readFile(filePath)
+	const offset = findInvalidUtf8(data)
+	if (offset === undefined) {
+		return { valid: true }
+	}
+	return { valid: false, firstInvalidByteOffset: offset, byteValue: data[offset] }
+}
@@ -0,0 +1,375 @@
+import fs from "fs/promises"
+import workerpool from "workerpool"
+
+/**
+ * Counts non-overlapping occurrences of a literal substring in a file without
+ * compiling a regular expression. Matches may span line boundaries.
+ *
+ * @param filePath - Path to the file to search
+ * @param needle - Literal text to count
+ * @returns Promise resolving to the number of occurrences, or 0 for an empty needle
+ */
+export async function countLiteral(filePath: string, needle: string): Promise<number> {
+	if (needle === "") {
+		return 0
+	}
+
+	const haystack = await fs.readFile(filePath)
+	const pattern = Buffer.from(needle, "utf8")
+
+	let count = 0
+	let index = haystack.indexOf(pattern)
+	while (index !== -1) {
+		count++
+		index = haystack.indexOf(pattern, index + pattern.length)
+	}
+	return count
+}
+
+export interface TextSearchOptions {
+	/** Match without regard to case (default: false) */
+	caseInsensitive?: boolean
+}
+
+export interface MatchSpan {
+	/** 1-based line number */
+	line: number
+	/** 0-based column of the first matched character */
+	startCol: number
+	/** 0-based column just past the last matched character */
+	endCol: number
+	/** Byte offset of the match in the file (only with `byteOffsets`) */
+	byteStart?: number
+	/** Byte offset just past the match in the file (only with `byteOffsets`) */
+	byteEnd?: number
+}
+
+/**
+ * Compiles a user pattern into a Unicode-aware regular expression. Every search
+ * and replace helper compiles through here so flag handling stays consistent.
+ *
+ * @param pattern - Regular expression source
+ * @param options - Search options
+ * @param global - Whether to set the "g" flag; leave it off for test() and exec() loops (default: true)
+ * @returns The compiled regular expression
+ */
+export function compilePattern(pattern: string, options: TextSearchOptions = {}, global: boolean = true): RegExp {
+	return new RegExp(pattern, (global ? "g" : "") + (options.caseInsensitive ? "iu" : "u"))
+}
+
+export interface SpanScan {
+	/** Spans not already passed to `emit` */
+	spans: MatchSpan[]
+	/** Whether scanning stopped at the deadline before the last line */
+	timedOut: boolean
+}
+
+/**
+ * Counts Unicode code points, so columns are character-based rather than byte- or UTF-16-based.
+ */
+function charLength(text: string): number {
+	return Array.from(text).length
+}
+
+/** Lines scanned between batches passed to scanSpans' `emit` */
+const SCAN_EMIT_INTERVAL = 1000
+
+/**
+ * Scans content line by line (LF-separated, with a trailing CR stripped) and
+ * returns a span for every non-empty match, stopping between lines once
+ * `deadline` (epoch milliseconds) has passed. With `emit`, spans are handed
+ * over in batches as scanning progresses, so a worker that is terminated
+ * mid-line has still reported the lines it finished.
+ *
+ * @param content - Text to scan
+ * @param source - Regular expression source, compiled with compilePattern
+ * @param flags - Regular expression flags, compiled with compilePattern
+ * @param byteOffsets - Whether to include file-absolute byte offsets
+ * @param deadline - Time after which no further lines are scanned
+ * @param emit - Optional. Receives batches of spans; they are then left out of the result
+ * @returns The remaining spans and whether the deadline cut the scan short
+ */
+export function scanSpans(
+	content: string,
+	source: string,
+	flags: string,
+	byteOffsets: boolean,
+	deadline: number,
+	emit?: (spans: MatchSpan[]) => void,
+): SpanScan {
+	const regex = new RegExp(source, flags)
+	let spans: MatchSpan[] = []
+	let lineStart = 0
+	let lineByteOffset = 0
+
+	for (let line = 1; lineStart <= content.length; line++) {
+		if (Date.now() > deadline) {
+			return { spans, timedOut: true }
+		}
+
+		const newline = content.indexOf("\n", lineStart)
+		const lineEnd = newline === -1 ? content.length : newline
+		const raw = content.slice(lineStart, lineEnd)
+		const text = raw.endsWith("\r") ? raw.slice(0, -1) : raw
+
+		// Columns and offsets are measured from the end of the previous match so long lines stay linear
+		let index = 0
+		let column = 0
+		let byteOffset = lineByteOffset
+		for (const match of text.matchAll(regex)) {
+			if (match[0].length === 0) continue
+
+			const gap = text.slice(index, match.index)
+			const startCol = column + charLength(gap)
+			const span: MatchSpan = { line, startCol, endCol: startCol + charLength(match[0]) }
+			if (byteOffsets) {
+				span.byteStart = byteOffset + Buffer.byteLength(gap)
+				span.byteEnd = span.byteStart + Buffer.byteLength(match[0])
+				byteOffset = span.byteEnd
+			}
+			index = match.index + match[0].length
+			column = span.endCol
+			spans.push(span)
+		}
+
+		if (emit && spans.length > 0 && line % SCAN_EMIT_INTERVAL === 0) {
+			emit(spans)
+			spans = []
+		}
+
+		lineByteOffset += Buffer.byteLength(raw) + 1
+		lineStart = lineEnd + 1
+	}
+
+	return { spans, timedOut: false }
+}
+
+/**
+ * Extra time the worker gets past `timeoutMs` to return its partial results
+ * before it is terminated, e.g. when a single line is still being matched.
+ */
+const SEARCH_WORKER_GRACE_MS = 500
+
+let searchPool: workerpool.Pool | null | undefined = undefined
+
+/**
+ * Runs scanSpans on a pooled worker (src/workers/findSpans.ts), so a
+ * pathological pattern can't block the extension host. Returns the spans
+ * gathered so far when the deadline passes. If the worker can't run, the scan
+ * falls back to this thread, where the deadline is still checked between lines.
+ */
+async function scanSpansInWorker(
+	content: string,
+	regex: RegExp,
+	byteOffsets: boolean,
+	timeoutMs: number,
+): Promise<SpanScan> {
+	const deadline = Date.now() + timeoutMs
+
+	// Lazily create the worker pool if it doesn't exist.
+	if (searchPool === undefined) {
+		searchPool = workerpool.pool(__dirname + "/workers/findSpans.js", { maxWorkers: 1 })
+	}
+
+	if (searchPool) {
+		const spans: MatchSpan[] = []
+		const collect = (batch: MatchSpan[]) => {
+			for (const span of batch) spans.push(span)
+		}
+
+		try {
+			const result: SpanScan = await searchPool
+				.exec("scanSpans", [content, regex.source, regex.flags, byteOffsets, deadline], { on: collect })
+				.timeout(timeoutMs + SEARCH_WORKER_GRACE_MS)
+			collect(result.spans)
+			return { spans, timedOut: result.timedOut }
+		} catch (error) {
+			// The pool terminates a timed-out worker and starts a fresh one for the next task
+			if (error instanceof workerpool.Promise.TimeoutError) {
+				return { spans, timedOut: true }
+			}
+			searchPool = null
+			console.error(error)
+		}
+	}
+
+	return scanSpans(content, regex.source, regex.flags, byteOffsets, deadline)
+}
+
+export interface FindSpansOptions extends TextSearchOptions {
+	/** "line" guarantees ascending line order; matches on the same line keep their order (default: "none") */
+	sortBy?: "line" | "none"
+	/** Keep only the first occurrence on each line (default: false) */
+	dedupLines?: boolean
+	/** Include file-absolute `byteStart` and `byteEnd` offsets for byte-based editing (default: false) */
+	byteOffsets?: boolean
+}
+
+export interface TimedFindSpansOptions extends FindSpansOptions {
+	/** Run the search on a worker and stop after this many milliseconds */
+	timeoutMs: number
+}
+
+export interface TimedMatchSpans {
+	/** The spans found before the search finished or was stopped */
+	spans: MatchSpan[]
+	/** Whether the search stopped at the deadline, so `spans` may be incomplete */
+	timedOut: boolean
+}
+
+/**
+ * Finds every regex match in a file and returns only its position, for
+ * highlighting search results without shipping line content.
+ *
+ * Patterns run on V8's backtracking engine, so a pathological pattern can take
+ * very long. With `timeoutMs` the search runs on a worker instead of the
+ * extension host and resolves to the spans found so far, with `timedOut: true`,
+ * once the deadline passes. The deadline is checked between lines; a worker
+ * stuck on a single line is terminated shortly after it.
+ *
+ * @param filePath - Path to the file to search
+ * @param pattern - Regular expression source, applied to each line
+ * @param options - Search, ordering, deduplication and timeout options
+ * @returns Promise resolving to one span per match occurrence (or per line with `dedupLines`), in file order;
+ * with `timeoutMs`, wrapped together with the `timedOut` flag
+ * @throws {RangeError} If timeoutMs is not a positive number
+ */
+export function findSpans(filePath: string, pattern: string, options: TimedFindSpansOptions): Promise<TimedMatchSpans>
+export function findSpans(filePath: string, pattern: string, options?: FindSpansOptions): Promise<MatchSpan[]>
+export async function findSpans(
+	filePath: string,
+	pattern: string,
+	options: FindSpansOptions & { timeoutMs?: number } = {},
+): Promise<MatchSpan[] | TimedMatchSpans> {
+	const { timeoutMs } = options
+	if (timeoutMs !== undefined && !(timeoutMs > 0 && Number.isFinite(timeoutMs))) {
+		throw new RangeError(`Invalid timeoutMs: ${timeoutMs}. Timeout must be a positive number.`)
+	}
+
+	const regex = compilePattern(pattern, options)
+	const content = await fs.readFile(filePath, "utf8")
+	const byteOffsets = options.byteOffsets ?? false
+	const scan =
+		timeoutMs === undefined
+			? scanSpans(content, regex.source, regex.flags, byteOffsets, Infinity)
+			: await scanSpansInWorker(content, regex, byteOffsets, timeoutMs)
+	let spans = scan.spans
+
+	if (options.sortBy === "line") {
+		// Array.prototype.sort is stable, so same-line spans keep their relative order
+		spans.sort((a, b) => a.line - b.line)
+	}
+	if (options.dedupLines) {
+		const seen = new Set<number>()
+		spans = spans.filter((span) => !seen.has(span.line) && seen.add(span.line))
+	}
+
+	return timeoutMs === undefined ? spans : { spans, timedOut: scan.timedOut }
+}
+
+export interface LineMatches {
+	/** 1-based line number */
+	line: number
+	/** Full text of the line */
+	content: string
+	/** Every occurrence on the line, in order */
+	matches: Array<Omit<MatchSpan, "line">>
+}
+
+/**
+ * Finds regex matches in a file grouped by line, so each matching line's
+ * content is returned once with all of its occurrences nested under it.
+ *
+ * This is the grouped counterpart to {@link findSpans}; use one or the other
+ * depending on whether the consumer wants per-occurrence or per-line results.
+ *
+ * @param filePath - Path to the file to search
+ * @param pattern - Regular expression source, applied to each line
+ * @param options - Search options
+ * @returns Promise resolving to one entry per matching line, in file order
+ */
+export async function findMatchesByLine(
+	filePath: string,
+	pattern: string,
+	options: TextSearchOptions = {},
+): Promise<LineMatches[]> {
+	const regex = compilePattern(pattern, options)
+	const content = await fs.readFile(filePath, "utf8")
+	const lines = content.split(/\r?\n/)
+	const results: LineMatches[] = []
+
+	for (const { line, ...columns } of scanSpans(content, regex.source, regex.flags, false, Infinity).spans) {
+		const last = results[results.length - 1]
+		if (last?.line === line) {
+			last.matches.push(columns)
+		} else {
+			results.push({ line, content: lines[line - 1], matches: [columns] })
+		}
+	}
+
+	return results
+}
+
+/**
+ * Counts the lines in a file that contain at least one regex match, for quick
+ * statistics such as "lines containing ERROR". A line with several matches
+ * counts once.
+ *
+ * @param filePath - Path to the file to search
+ * @param pattern - Regular expression source, applied to each line
+ * @param options - Search options
+ * @returns Promise resolving to the number of matching lines
+ */
+export async function countLinesMatching(
+	filePath: string,
+	pattern: string,
+	options: TextSearchOptions = {},
+): Promise<number> {
+	// Non-global so test() doesn't carry lastIndex from one line to the next
+	const regex = compilePattern(pattern, options, false)
+	const lines = (await fs.readFile(filePath, "utf8")).split(/\r?\n/)
+
+	return lines.filter((text) => regex.test(text)).length
+}
+
+export interface CaptureMatch {
+	/** 1-based line number */
+	line: number
+	/** The full match followed by each numbered group; groups that didn't participate are "" */
+	groups: string[]
+	/** Named groups keyed by name, present only when the pattern defines any */
+	named?: Record<string, string>
+}
+
+/**
+ * Extracts captured fields (timestamps, IDs, ...) from the first match on each
+ * line of a file. Lines without a match are skipped. Named groups may be written
+ * as `(?<name>...)` or in the Python/Rust `(?P<name>...)` form.
+ *
+ * @param filePath - Path to the file to search
+ * @param pattern - Regular expression source with capture groups, applied to each line
+ * @param options - Search options
+ * @returns Promise resolving to the captures for each matching line, in file order
+ */
+export async function extractCaptures(
+	filePath: string,
+	pattern: string,
+	options: TextSearchOptions = {},
+): Promise<CaptureMatch[]> {
+	const regex = compilePattern(pattern.replace(/\(\?P</g, "(?<"), options, false)
+	const lines = (await fs.readFile(filePath, "utf8")).split(/\r?\n/)
+	const results: CaptureMatch[] = []
+
+	lines.forEach((text, index) => {
+		const match = regex.exec(text)
+		if (!match) return
+
+		const capture: CaptureMatch = { line: index + 1, groups: Array.from(match, (group) => group ?? "") }
+		if (match.groups) {
+			capture.named = Object.fromEntries(Object.entries(match.groups).map(([name, value]) => [name, value ?? ""]))
+		}
+		results.push(capture)
+	})
+
+	return results
+}
@@ -0,0 +1,699 @@
+import { createReadStream } from "fs"
+import fs, { type FileHandle } from "fs/promises"
+import pLimit from "p-limit"
+import stripAnsiString from "strip-ansi"
+
+import { writeFileAtomic, writeFileAtomicStream } from "../../utils/fs"
+import { getBatchConcurrency } from "./batch-concurrency"
+import { compilePattern } from "./text-search"
+
+/**
+ * Removes ANSI escape sequences (colors, cursor movement, etc.) from a buffer of
+ * captured terminal output.
+ *
+ * @param data - Raw file content
+ * @returns The decoded text without escape sequences
+ */
+export function stripAnsiFromBuffer(data: Buffer): string {
+	return stripAnsiString(data.toString("utf8"))
+}
+
+/**
+ * Reads a file (typically a captured terminal log) and removes ANSI escape sequences.
+ *
+ * @param filePath - Path to the file to read
+ * @returns Promise resolving to the clean text
+ */
+export async function stripAnsi(filePath: string): Promise<string> {
+	return stripAnsiFromBuffer(await fs.readFile(filePath))
+}
+
+/**
+ * Removes ANSI escape sequences from a file and writes the result back atomically.
+ * The file is left untouched when it contains no escape sequences.
+ *
+ * @param filePath - Path to the file to clean
+ * @returns Promise resolving to the number of bytes removed
+ */
+export async function stripAnsiInPlace(filePath: string): Promise<number> {
+	const original = await fs.readFile(filePath)
+	const cleaned = Buffer.from(stripAnsiFromBuffer(original), "utf8")
+
+	if (cleaned.equals(original)) {
+		return 0
+	}
+
+	await writeFileAtomic(filePath, cleaned)
+	return original.length - cleaned.length
+}
+
+/**
+ * Measures the display width of a leading whitespace run using tab stops.
+ */
+function leadingWhitespaceColumns(whitespace: string, tabWidth: number): number {
+	let columns = 0
+	for (const char of whitespace) {
+		columns = char === "\t" ? columns + tabWidth - (columns % tabWidth) : columns + 1
+	}
+	return columns
+}
+
+/**
+ * Rewrites the leading whitespace of every line in a file, keeping line endings
+ * and everything after the indentation untouched.
+ *
+ * @returns Promise resolving to the number of lines changed
+ */
+async function rewriteIndentation(filePath: string, rewrite: (whitespace: string) => string): Promise<number> {
+	const content = await fs.readFile(filePath, "utf8")
+	let linesChanged = 0
+
+	const lines = content.split("\n").map((line) => {
+		const whitespace = line.match(/^[ \t]*/)![0]
+		const replacement = rewrite(whitespace)
+		if (replacement === whitespace) {
+			return line
+		}
+		linesChanged++
+		return replacement + line.slice(whitespace.length)
+	})
+
+	if (linesChanged > 0) {
+		await writeFileAtomic(filePath, lines.join("\n"))
+	}
+	return linesChanged
+}
+
+function validateTabWidth(tabWidth: number): void {
+	if (!Number.isInteger(tabWidth) || tabWidth < 1) {
+		throw new RangeError(`Invalid tabWidth: ${tabWidth}. Tab width must be a positive integer.`)
+	}
+}
+
+/**
+ * Converts tab indentation to spaces, honoring tab stops. Tabs after the first
+ * non-whitespace character of a line are left alone.
+ *
+ * @param filePath - Path to the file to rewrite
+ * @param tabWidth - Number of columns per tab stop
+ * @returns Promise resolving to the number of lines changed
+ */
+export async function convertTabsToSpaces(filePath: string, tabWidth: number): Promise<number> {
+	validateTabWidth(tabWidth)
+	return rewriteIndentation(filePath, (whitespace) =>
+		whitespace.includes("\t") ? " ".repeat(leadingWhitespaceColumns(whitespace, tabWidth)) : whitespace,
+	)
+}
+
+/**
+ * Converts space indentation to tabs. Indentation that is not a multiple of
+ * `tabWidth` keeps the remainder as spaces. Whitespace after the first
+ * non-whitespace character of a line is left alone.
+ *
+ * @param filePath - Path to the file to rewrite
+ * @param tabWidth - Number of spaces that make up one tab
+ * @returns Promise resolving to the number of lines changed
+ */
+export async function convertSpacesToTabs(filePath: string, tabWidth: number): Promise<number> {
+	validateTabWidth(tabWidth)
+	return rewriteIndentation(filePath, (whitespace) => {
+		const columns = leadingWhitespaceColumns(whitespace, tabWidth)
+		return "\t".repeat(Math.floor(columns / tabWidth)) + " ".repeat(columns % tabWidth)
+	})
+}
+
+/**
+ * Expands every tab to spaces up to the next tab stop, so text lines up the same
+ * way regardless of the viewer's tab settings. Columns count code points and
+ * reset after each line break.
+ *
+ * @param text - The text to expand
+ * @param tabWidth - Number of columns per tab stop
+ * @returns The text with tabs replaced by spaces
+ */
+export function expandTabs(text: string, tabWidth: number): string {
+	validateTabWidth(tabWidth)
+
+	let result = ""
+	let column = 0
+	for (const char of text) {
+		if (char === "\t") {
+			const spaces = tabWidth - (column % tabWidth)
+			result += " ".repeat(spaces)
+			column += spaces
+		} else {
+			result += char
+			column = char === "\n" || char === "\r" ? 0 : column + 1
+		}
+	}
+	return result
+}
+
+/**
+ * Reads a file with tabs expanded to the given tab stops, for display only.
+ * The result must not be written back, since it no longer matches the file's
+ * real indentation.
+ *
+ * @param filePath - Path to the file to read
+ * @param tabWidth - Number of columns per tab stop
+ * @returns Promise resolving to the content with tabs expanded
+ */
+export async function readFileForDisplay(filePath: string, tabWidth: number): Promise<string> {
+	validateTabWidth(tabWidth)
+	return expandTabs(await fs.readFile(filePath, "utf8"), tabWidth)
+}
+
+/**
+ * Reads a file and wraps each line to at most `width` characters for display
+ * in a fixed-width panel. Lines break at the last whitespace that fits, and
+ * words longer than `width` are hard-broken. Blank lines are preserved.
+ *
+ * @param filePath - Path to the file to read
+ * @param width - Maximum number of characters per wrapped line
+ * @returns Promise resolving to the wrapped lines
+ */
+export async function wrapLines(filePath: string, width: number): Promise<string[]> {
+	if (!Number.isInteger(width) || width < 1) {
+		throw new RangeError(`Invalid width: ${width}. Width must be a positive integer.`)
+	}
+
+	const content = await fs.readFile(filePath, "utf8")
+	if (content === "") {
+		return []
+	}
+
+	const lines = content.split(/\r?\n/)
+	if (content.endsWith("\n")) {
+		lines.pop()
+	}
+
+	return lines.flatMap((line) => wrapLine(line, width))
+}
+
+function wrapLine(line: string, width: number): string[] {
+	// Work on code points so surrogate pairs are never split
+	let chars = Array.from(line)
+	const wrapped: string[] = []
+
+	while (chars.length > width) {
+		// Breaking inside the leading indentation would emit an empty segment and drop the indent
+		let indent = 0
+		while (indent < chars.length && /\s/.test(chars[indent])) {
+			indent++
+		}
+
+		let breakAt = -1
+		for (let i = width; i > indent; i--) {
+			if (/\s/.test(chars[i])) {
+				breakAt = i
+				break
+			}
+		}
+
+		if (breakAt === -1) {
+			wrapped.push(chars.slice(0, width).join(""))
+			chars = chars.slice(width)
+		} else {
+			wrapped.push(chars.slice(0, breakAt).join("").trimEnd())
+			let next = breakAt
+			while (next < chars.length && /\s/.test(chars[next])) {
+				next++
+			}
+			chars = chars.slice(next)
+		}
+	}
+
+	if (chars.length > 0 || wrapped.length === 0) {
+		wrapped.push(chars.join(""))
+	}
+	return wrapped
+}
+
+export interface NormalizeWhitespaceOptions {
+	/** Collapse runs of spaces and tabs between words into a single space */
+	collapseRuns?: boolean
+	/** Remove whitespace at the end of each line */
+	trimTrailing?: boolean
+	/** Remove indentation at the start of each line */
+	trimLeading?: boolean
+}
+
+/**
+ * Applies the selected whitespace cleanups to every line of a file and writes
+ * the result back atomically. Line endings are preserved. Unless `trimLeading`
+ * is set, indentation is left alone, so `collapseRuns` only affects whitespace
+ * after the first non-whitespace character.
+ *
+ * @param filePath - Path to the file to rewrite
+ * @param options - Which transforms to apply; none are applied by default
+ * @returns Promise resolving to the number of lines changed
+ */
+export async function normalizeWhitespace(filePath: string, options: NormalizeWhitespaceOptions = {}): Promise<number> {
+	const content = await fs.readFile(filePath, "utf8")
+	let linesChanged = 0
+
+	const lines = content.split("\n").map((line) => {
+		const hasCarriageReturn = line.endsWith("\r")
+		let body = hasCarriageReturn ? line.slice(0, -1) : line
+		if (options.trimTrailing) {
+			body = body.replace(/[ \t]+$/, "")
+		}
+
+		const leading = body.match(/^[ \t]*/)![0]
+		const indentation = options.trimLeading ? "" : leading
+		let rest = body.slice(leading.length)
+		if (options.collapseRuns) {
+			rest = rest.replace(/[ \t]{2,}/g, " ")
+		}
+
+		const normalized = indentation + rest + (hasCarriageReturn ? "\r" : "")
+		if (normalized !== line) {
+			linesChanged++
+		}
+		return normalized
+	})
+
+	if (linesChanged > 0) {
+		await writeFileAtomic(filePath, lines.join("\n"))
+	}
+	return linesChanged
+}
+
+/**
+ * Locates a JSON.parse failure as a 1-based line and column, when the runtime
+ * reports an offset in its error message.
+ */
+function jsonErrorLocation(message: string, content: string): { line: number; column: number } | undefined {
+	const explicit = message.match(/\(line (\d+) column (\d+)\)/)
+	if (explicit) {
+		return { line: Number(explicit[1]), column: Number(explicit[2]) }
+	}
+
+	const position = message.match(/at position (\d+)/)
+	const offset = position ? Number(position[1]) : /end of JSON input/.test(message) ? content.length : undefined
+	if (offset === undefined) {
+		return undefined
+	}
+
+	return offsetLocation(content, offset)
+}
+
+/**
+ * Converts a character offset into a 1-based line and column.
+ */
+function offsetLocation(content: string, offset: number): { line: number; column: number } {
+	const before = content.slice(0, offset).split("\n")
+	return { line: before.length, column: before[before.length - 1].length + 1 }
+}
+
+/**
+ * Scans already-validated JSON for content that JSON.parse followed by
+ * JSON.stringify would silently change: numbers that don't survive the round
+ * trip (e.g. `1.0` or integers beyond 2^53) and duplicate object keys, where
+ * only the last value is kept.
+ *
+ * @returns The offending token and its offset, or undefined if the round trip is lossless
+ */
+function findLossyJson(content: string): { reason: string; offset: number } | undefined {
+	// One entry per open container: the keys seen so far for objects, null for arrays
+	const containers: Array<Set<string> | null> = []
+	let expectKey = false
+
+	for (let i = 0; i < content.length; i++) {
+		const char = content[i]
+		if (char === "{") {
+			containers.push(new Set())
+			expectKey = true
+		} else if (char === "[") {
+			containers.push(null)
+			expectKey = false
+		} else if (char === "}" || char === "]") {
+			containers.pop()
+		} else if (char === ",") {
+			expectKey = containers[containers.length - 1] != null
+		} else if (char === ":") {
+			expectKey = false
+		} else if (char === '"') {
+			let end = i + 1
+			while (content[end] !== '"') {
+				end += content[end] === "\\" ? 2 : 1
+			}
+			const keys = containers[containers.length - 1]
+			if (expectKey && keys) {
+				const key: string = JSON.parse(content.slice(i, end + 1))
+				if (keys.has(key)) {
+					return { reason: `duplicate key ${content.slice(i, end + 1)}`, offset: i }
+				}
+				keys.add(key)
+			}
+			i = end
+		} else if (char === "-" || (char >= "0" && char <= "9")) {
+			const token = /-?\d+(?:\.\d+)?(?:[eE][+-]?\d+)?/y
+			token.lastIndex = i
+			const number = token.exec(content)![0]
+			if (String(Number(number)) !== number) {
+				return { reason: `number ${number} would be rewritten as ${String(Number(number))}`, offset: i }
+			}
+			i += number.length - 1
+		}
+	}
+
+	return undefined
+}
+
+/**
+ * Validates and pretty-prints a JSON file, writing the result back atomically
+ * with a trailing newline. A leading byte order mark is kept. The file is left
+ * untouched when it isn't valid JSON or when reformatting would change its data.
+ *
+ * @param filePath - Path to the JSON file
+ * @param indent - Number of spaces per indentation level (default: 2)
+ * @returns Promise resolving to the number of bytes written
+ * @throws {Error} With code "EJSON" and, when known, the `line` and `column` of the syntax error
+ * @throws {Error} With code "EJSON_LOSSY", `line` and `column` if a number wouldn't round-trip or a key is duplicated
+ */
+export async function formatJson(filePath: string, indent: number = 2): Promise<number> {
+	if (!Number.isInteger(indent) || indent < 0 || indent > 10) {
+		throw new RangeError(`Invalid indent: ${indent}. Indent must be an integer between 0 and 10.`)
+	}
+
+	const raw = await fs.readFile(filePath, "utf8")
+	const bom = raw.startsWith("\uFEFF") ? "\uFEFF" : ""
+	const content = raw.slice(bom.length)
+
+	let parsed: unknown
+	try {
+		parsed = JSON.parse(content)
+	} catch (error) {
+		const message = error instanceof Error ? error.message : String(error)
+		const location = jsonErrorLocation(message, content)
+		const where = location ? ` at line ${location.line}, column ${location.column}` : ""
+		throw Object.assign(new Error(`Invalid JSON in ${filePath}${where}: ${message}`), {
+			code: "EJSON",
+			...location,
+		})
+	}
+
+	const lossy = findLossyJson(content)
+	if (lossy) {
+		const location = offsetLocation(content, lossy.offset)
+		throw Object.assign(
+			new Error(
+				`Refusing to format ${filePath}: ${lossy.reason} at line ${location.line}, column ${location.column}`,
+			),
+			{ code: "EJSON_LOSSY", ...location },
+		)
+	}
+
+	const formatted = Buffer.from(bom + JSON.stringify(parsed, null, indent) + "\n", "utf8")
+	await writeFileAtomic(filePath, formatted)
+	return formatted.length
+}
+
+/**
+ * Checks whether a file ends with a line feed by reading only its last byte.
+ *
+ * @param filePath - Path to the file to check
+ * @returns Promise resolving to true if the final byte is "\n"; false for empty files
+ */
+export async function hasTrailingNewline(filePath: string): Promise<boolean> {
+	const handle = await fs.open(filePath, "r")
+	try {
+		const { size } = await handle.stat()
+		if (size === 0) {
+			return false
+		}
+
+		const lastByte = Buffer.alloc(1)
+		await handle.read(lastByte, 0, 1, size - 1)
+		return lastByte[0] === 0x0a
+	} finally {
+		await handle.close()
+	}
+}
+
+/** Bytes read at a time while looking for a file's first line break */
+const LINE_BREAK_PROBE_SIZE = 64 * 1024
+
+/**
+ * Appends a final newline to a file that lacks one, using CRLF when the file's
+ * first line break is CRLF and LF otherwise. Empty files are left alone, as are
+ * files ending in a lone "\r", which already end with a CR line break.
+ *
+ * Unlike the other in-place helpers this appends to the file directly instead
+ * of going through writeFileAtomic, so it is not atomic. An interrupted append
+ * can at worst leave the newline missing.
+ *
+ * @param filePath - Path to the file to fix
+ * @returns Promise resolving to true if a newline was appended
+ */
+export async function ensureTrailingNewline(filePath: string): Promise<boolean> {
+	if (await hasTrailingNewline(filePath)) {
+		return false
+	}
+
+	const handle = await fs.open(filePath, "a+")
+	try {
+		const { size } = await handle.stat()
+		if (size === 0) {
+			return false
+		}
+
+		const lastByte = Buffer.alloc(1)
+		await handle.read(lastByte, 0, 1, size - 1)
+		if (lastByte[0] === 0x0d) {
+			return false
+		}
+
+		const lineEnding = (await firstLineBreakIsCrlf(handle, size)) ? "\r\n" : "\n"
+		await handle.write(lineEnding)
+		return true
+	} finally {
+		await handle.close()
+	}
+}
+
+/**
+ * Reads forward from the start of a file until its first "\n" and reports
+ * whether it is preceded by "\r".
+ */
+async function firstLineBreakIsCrlf(handle: FileHandle, size: number): Promise<boolean> {
+	const buffer = Buffer.alloc(Math.min(LINE_BREAK_PROBE_SIZE, size))
+	let previousByte = -1
+
+	for (let position = 0; position < size; position += buffer.length) {
+		const { bytesRead } = await handle.read(buffer, 0, buffer.length, position)
+		const index = buffer.subarray(0, bytesRead).indexOf(0x0a)
+		if (index !== -1) {
+			return (index > 0 ? buffer[index - 1] : previousByte) === 0x0d
+		}
+		if (bytesRead === 0) {
+			break
+		}
+		previousByte = buffer[bytesRead - 1]
+	}
+	return false
+}
+
+/**
+ * Applies a global regex replacement to a single line (without its line ending).
+ */
+function replaceInLine(line: string, regex: RegExp, replacement: string): { text: string; count: number } {
+	const count = Array.from(line.matchAll(regex)).length
+	return { text: count > 0 ? line.replace(regex, replacement) : line, count }
+}
+
+export interface BasicFormattingOptions {
+	/** Remove spaces and tabs at the end of each line */
+	trimTrailing?: boolean
+	/** Terminate the last line if it isn't already */
+	ensureFinalNewline?: boolean
+	/** Rewrite every line ending to LF or CRLF; null or omitted keeps the existing endings */
+	normalizeLineEndings?: "lf" | "crlf" | null
+}
+
+const LINE_ENDINGS = { lf: "\n", crlf: "\r\n" }
+
+/**
+ * Applies the usual on-save cleanups in a single read and atomic write. A final
+ * newline uses the file's existing convention (CRLF if it has any CRLF endings,
+ * LF otherwise) unless `normalizeLineEndings` overrides it. The file is left
+ * untouched when nothing changes.
+ *
+ * @param filePath - Path to the file to format
+ * @param options - Which cleanups to apply; none are applied by default
+ * @returns Promise resolving to the number of lines changed and the bytes written (0 when unchanged)
+ */
+export async function applyBasicFormatting(
+	filePath: string,
+	options: BasicFormattingOptions = {},
+): Promise<{ linesChanged: number; bytesWritten: number }> {
+	const content = await fs.readFile(filePath, "utf8")
+	const override = options.normalizeLineEndings ? LINE_ENDINGS[options.normalizeLineEndings] : undefined
+	const finalNewline = override ?? (content.includes("\r\n") ? "\r\n" : "\n")
+
+	const lines = content.split("\n")
+	const tail = lines.length - 1
+	let linesChanged = 0
+
+	const formatted = lines.map((line, index) => {
+		const hasCarriageReturn = index < tail && line.endsWith("\r")
+		let body = hasCarriageReturn ? line.slice(0, -1) : line
+		if (options.trimTrailing) {
+			body = body.replace(/[ \t]+$/, "")
+		}
+
+		let ending = ""
+		if (index < tail) {
+			ending = override ?? (hasCarriageReturn ? "\r\n" : "\n")
+		} else if (options.ensureFinalNewline && body.length > 0) {
+			ending = finalNewline
+		}
+
+		const original = index < tail ? line + "\n" : line
+		if (body + ending !== original) {
+			linesChanged++
+		}
+		return body + ending
+	})
+
+	if (linesChanged === 0) {
+		return { linesChanged, bytesWritten: 0 }
+	}
+
+	const output = Buffer.from(formatted.join(""), "utf8")
+	await writeFileAtomic(filePath, output)
+	return { linesChanged, bytesWritten: output.length }
+}
+
+/**
+ * Output is buffered until it reaches this many characters, so each written
+ * chunk is at most this plus one line
+ */
+const STREAMING_FLUSH_SIZE = 64 * 1024
+
+/**
+ * Applies a regex replacement to every line of a file while streaming it, so
+ * memory use is bounded by the longest line rather than the file size. The
+ * result is written to a temporary file and renamed over the original; with no
+ * replacements the temporary file is discarded and the original is untouched.
+ *
+ * The pattern is applied to each line separately (without its line ending), so
+ * matches can't span lines; use an in-memory replacement for multiline patterns.
+ * Line endings are preserved. `replacement` supports the usual `$1`/`$<name>`
+ * substitutions.
+ *
+ * @param filePath - Path to the file to rewrite
+ * @param pattern - Regular expression source, applied to each line
+ * @param replacement - Replacement string
+ * @returns Promise resolving to the number of replacements made
+ */
+export async function replaceInFileStreaming(filePath: string, pattern: string, replacement: string): Promise<number> {
+	const regex = compilePattern(pattern)
+	let replacements = 0
+
+	const replaceLine = (line: string) => {
+		const terminator = line.match(/\r?\n$/)?.[0] ?? ""
+		const { text, count } = replaceInLine(line.slice(0, line.length - terminator.length), regex, replacement)
+		if (count === 0) {
+			return line
+		}
+		replacements += count
+		return text + terminator
+	}
+
+	async function* transformedChunks() {
+		let pending = ""
+		let output = ""
+
+		for await (const chunk of createReadStream(filePath, { encoding: "utf8" })) {
+			pending += chunk
+			let start = 0
+			let newline = pending.indexOf("\n")
+			while (newline !== -1) {
+				output += replaceLine(pending.slice(start, newline + 1))
+				start = newline + 1
+				newline = pending.indexOf("\n", start)
+
+				if (output.length >= STREAMING_FLUSH_SIZE) {
+					yield output
+					output = ""
+				}
+			}
+			pending = pending.slice(start)
+		}
+
+		yield output + (pending ? replaceLine(pending) : "")
+	}
+
+	await writeFileAtomicStream(filePath, transformedChunks(), () => replacements > 0)
+	return replacements
+}
+
+/**
+ * Default number of changed lines included in each file's preview
+ */
+const DEFAULT_PREVIEW_LINES = 20
+
+export interface ReplacePreviewOptions {
+	/** Maximum number of changed lines to include per file (default: 20) */
+	maxPreviewLines?: number
+}
+
+export interface FileReplacePreview {
+	path: string
+	/** Number of replacements the apply step would make */
+	changeCount: number
+	/** The first changed lines, with their 1-based line numbers */
+	preview: Array<{ line: number; before: string; after: string }>
+}
+
+/**
+ * Previews a regex replacement across several files without writing anything,
+ * so a rename can be reviewed before it is applied. Replacements follow the
+ * same line-scoped rules as {@link replaceInFileStreaming}, so `changeCount`
+ * matches what applying it would report.
+ *
+ * @param paths - Files to preview
+ * @param pattern - Regular expression source, applied to each line
+ * @param replacement - Replacement string
+ * @param options - Preview options
+ * @returns Promise resolving to one entry per path, in input order
+ */
+export async function previewReplaceInFiles(
+	paths: string[],
+	pattern: string,
+	replacement: string,
+	options: ReplacePreviewOptions = {},
+): Promise<FileReplacePreview[]> {
+	const { maxPreviewLines = DEFAULT_PREVIEW_LINES } = options
+	if (!Number.isInteger(maxPreviewLines) || maxPreviewLines < 0) {
+		throw new RangeError(`Invalid maxPreviewLines: ${maxPreviewLines}. Must be a non-negative integer.`)
+	}
+
+	const regex = compilePattern(pattern)
+	const limit = pLimit(getBatchConcurrency())
+
+	return Promise.all(
+		paths.map((filePath) =>
+			limit(async () => {
+				const lines = (await fs.readFile(filePath, "utf8")).split("\n")
+				const result: FileReplacePreview = { path: filePath, changeCount: 0, preview: [] }
+
+				lines.forEach((line, index) => {
+					const before = line.endsWith("\r") ? line.slice(0, -1) : line
+					const { text, count } = replaceInLine(before, regex, replacement)
+					if (count === 0) {
+						return
+					}
+					result.changeCount += count
+					if (result.preview.length < maxPreviewLines) {
+						result.preview.push({ line: index + 1, before, after: text })
+					}
+				})
+
+				return result
+			}),
+		),
+	)
+}
@@ -0,0 +1,230 @@
+import { createReadStream } from "fs"
+import fs from "fs/promises"
+
+import { countTextTokens } from "../../utils/tiktoken"
+
+export interface TokenChunk {
+	/** 1-based first line of the chunk */
+	startLine: number
+	/** 1-based last line of the chunk (inclusive) */
+	endLine: number
+	/** Chunk lines joined with "\n" */
+	content: string
+	/** Token count of the chunk (sum of its line counts) */
+	tokens: number
+}
+
+export interface TokenChunkOptions {
+	/** Count tokens with the BPE tokenizer instead of the chars/4 heuristic (default: false) */
+	useTokenizer?: boolean
+	/** Estimate with estimateCodeTokens instead of chars/4; ignored with `useTokenizer` (default: false) */
+	codeMode?: boolean
+	/** Number of trailing lines of each chunk to repeat at the start of the next one (default: 0) */
+	overlapLines?: number
+}
+
+// Mirrors the o200k pre-tokenizer: words split at case changes (with one leading
+// symbol or space), 1-3 digit groups, symbol runs and whitespace runs
+const CODE_PRE_TOKEN = new RegExp(
+	[
+		"[^\\r\\n\\p{L}\\p{N}]?[\\p{Lu}\\p{Lt}\\p{Lm}\\p{Lo}\\p{M}]*[\\p{Ll}\\p{Lm}\\p{Lo}\\p{M}]+",
+		"[^\\r\\n\\p{L}\\p{N}]?[\\p{Lu}\\p{Lt}\\p{Lm}\\p{Lo}\\p{M}]+[\\p{Ll}\\p{Lm}\\p{Lo}\\p{M}]*",
+		"\\p{N}{1,3}",
+		" ?[^\\s\\p{L}\\p{N}]+[\\r\\n/]*",
+		"\\s*[\\r\\n]+",
+		"\\s+(?!\\S)",
+		"\\s+",
+	].join("|"),
+	"gu",
+)
+
+/** Characters per token assumed for pre-tokens too long to be a single BPE token */
+const CODE_CHARS_PER_TOKEN = 8
+
+/**
+ * Estimates tokens for source code without a BPE model. Text is split the way
+ * the tokenizer pre-splits it and each piece counts as one token, or more when
+ * it is long. This is closer than chars/4 on code, where indentation runs and
+ * short symbols make characters per token vary widely.
+ *
+ * @param text - The text to estimate
+ * @returns The estimated token count
+ */
+export function estimateCodeTokens(text: string): number {
+	let tokens = 0
+	for (const [piece] of text.matchAll(CODE_PRE_TOKEN)) {
+		tokens += Math.max(1, Math.ceil(piece.trim().length / CODE_CHARS_PER_TOKEN))
+	}
+	return tokens
+}
+
+/**
+ * Picks the counting method selected by the options.
+ */
+function getTokenCounter(options: Pick<TokenChunkOptions, "useTokenizer" | "codeMode">): (text: string) => number {
+	if (options.useTokenizer) {
+		return countTextTokens
+	}
+	return options.codeMode ? estimateCodeTokens : (text) => Math.ceil(text.length / 4)
+}
+
+/**
+ * Splits content into lines, ignoring the empty line after a trailing newline.
+ */
+function splitLines(content: string): string[] {
+	const lines = content.split(/\r?\n/)
+	if (lines[lines.length - 1] === "") {
+		lines.pop()
+	}
+	return lines
+}
+
+/**
+ * Splits content into chunks that each stay within a token budget, never
+ * splitting a line. Lines are accumulated greedily; a single line that exceeds
+ * the budget on its own is emitted as its own chunk.
+ *
+ * With `overlapLines`, each chunk after the first starts with the last lines of
+ * the previous chunk. Overlap is reduced when it would leave no room for new
+ * lines, so every chunk always advances through the file.
+ *
+ * @param content - The text to split
+ * @param maxTokens - Token budget per chunk
+ * @param options - Chunking options
+ * @returns Chunks covering every line in order
+ */
+export function chunkContentByTokens(
+	content: string,
+	maxTokens: number,
+	options: TokenChunkOptions = {},
+): TokenChunk[] {
+	if (!Number.isFinite(maxTokens) || maxTokens < 1) {
+		throw new RangeError(`Invalid maxTokens: ${maxTokens}. Token budget must be at least 1.`)
+	}
+
+	const { overlapLines = 0 } = options
+	if (!Number.isInteger(overlapLines) || overlapLines < 0) {
+		throw new RangeError(`Invalid overlapLines: ${overlapLines}. Overlap must be a non-negative integer.`)
+	}
+
+	const countTokens = getTokenCounter(options)
+	const lines = splitLines(content)
+	// Each line is counted with its line break
+	const lineTokens = lines.map((line) => countTokens(line + "\n"))
+
+	const chunks: TokenChunk[] = []
+	// Pending chunk is lines[start, end); lines before emittedUntil already belong to a chunk
+	let start = 0
+	let tokens = 0
+	let emittedUntil = 0
+
+	const emit = (end: number) => {
+		chunks.push({
+			startLine: start + 1,
+			endLine: end,
+			content: lines.slice(start, end).join("\n"),
+			tokens,
+		})
+		emittedUntil = end
+	}
+
+	for (let end = 0; end < lines.length; end++) {
+		const next = lineTokens[end]
+
+		if (tokens + next > maxTokens) {
+			if (end > emittedUntil) {
+				const chunkStart = start
+				emit(end)
+				start = Math.max(end - overlapLines, chunkStart)
+				tokens = lineTokens.slice(start, end).reduce((sum, count) => sum + count, 0)
+			}
+
+			// The pending lines are now all overlap; drop them as needed so the next
+			// line fits. A line over budget on its own still gets emitted alone.
+			while (start < end && tokens + next > maxTokens) {
+				tokens -= lineTokens[start]
+				start++
+			}
+		}
+
+		tokens += next
+	}
+
+	if (lines.length > emittedUntil) {
+		emit(lines.length)
+	}
+
+	return chunks
+}
+
+/**
+ * Reads a file and splits it into chunks that each stay within a token budget.
+ *
+ * @param filePath - Path to the file to split
+ * @param maxTokens - Token budget per chunk
+ * @param options - Chunking options
+ * @returns Promise resolving to chunks covering every line in order
+ */
+export async function chunkFileByTokens(
+	filePath: string,
+	maxTokens: number,
+	options: TokenChunkOptions = {},
+): Promise<TokenChunk[]> {
+	return chunkContentByTokens(await fs.readFile(filePath, "utf8"), maxTokens, options)
+}
+
+/**
+ * Counts tokens for each line of a file (including its line break), e.g. to
+ * render a per-line token cost heat map. The shared encoder is reused across
+ * all lines.
+ *
+ * @param filePath - Path to the file to analyze
+ * @param options - Counting options; only `useTokenizer` and `codeMode` apply
+ * @returns Promise resolving to one token count per line, in order
+ */
+export async function tokensPerLine(
+	filePath: string,
+	options: Pick<TokenChunkOptions, "useTokenizer" | "codeMode"> = {},
+): Promise<number[]> {
+	const countTokens = getTokenCounter(options)
+	const lines = splitLines(await fs.readFile(filePath, "utf8"))
+	return lines.map((line) => countTokens(line + "\n"))
+}
+
+/**
+ * Counts the tokens in a file while streaming it, without building a string of
+ * the whole file. With the tokenizer or `codeMode`, each chunk is cut at its
+ * last line break so no token is split across chunks; the total can still
+ * differ slightly from counting the file in one piece where runs of line breaks
+ * straddle a cut. The chars/4 count matches the whole-file estimate exactly.
+ *
+ * @param filePath - Path to the file to count
+ * @param options - Counting options; only `useTokenizer` and `codeMode` apply
+ * @returns Promise resolving to the file's token count
+ */
+export async function countTokensInFile(
+	filePath: string,
+	options: Pick<TokenChunkOptions, "useTokenizer" | "codeMode"> = {},
+): Promise<number> {
+	const perCharacter = !options.useTokenizer && !options.codeMode
+	const countTokens = getTokenCounter(options)
+	let tokens = 0
+	let characters = 0
+	let pending = ""
+
+	for await (const chunk of createReadStream(filePath, { encoding: "utf8" })) {
+		if (perCharacter) {
+			characters += (chunk as string).length
+			continue
+		}
+
+		pending += chunk
+		const cut = pending.lastIndexOf("\n") + 1
+		if (cut > 0) {
+			tokens += countTokens(pending.slice(0, cut))
+			pending = pending.slice(cut)
+		}
+	}
+
+	return perCharacter ? Math.ceil(characters / 4) : tokens + countTokens(pending)
+}
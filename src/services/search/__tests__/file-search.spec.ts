@@ -97,4 +97,49 @@ describe("file-search", () => {
 			expect(limit).toBe(10000)
 		})
 	})
+
+	describe("fuzzyMatchFiles", () => {
+		const file = (filePath: string) => ({ path: filePath, type: "file" as const, label: filePath.split("/").pop() })
+
+		it("should match like plain fzf when no options are given", async () => {
+			const { fuzzyMatchFiles } = await import("../file-search")
+			const items = [file("src/user_service.rs"), file("src/status.rs"), file("README.md")]
+
+			const results = fuzzyMatchFiles(items, "sus", 20).map((item) => item.path)
+
+			expect(results).toContain("src/user_service.rs")
+			expect(results).toContain("src/status.rs")
+			expect(results).not.toContain("README.md")
+		})
+
+		it("should drop results scoring below minScore", async () => {
+			const { fuzzyMatchFiles } = await import("../file-search")
+			// "sus" only matches mid-word here, so it earns no boundary bonuses
+			const items = [file("src/user_service.rs"), file("xsxxxxuxxxxxsx.txt")]
+
+			expect(fuzzyMatchFiles(items, "sus", 20)).toHaveLength(2)
+			expect(fuzzyMatchFiles(items, "sus", 20, { minScore: 50 }).map((item) => item.path)).toEqual([
+				"src/user_service.rs",
+			])
+			expect(fuzzyMatchFiles(items, "sus", 20, { minScore: Number.MAX_SAFE_INTEGER })).toEqual([])
+		})
+
+		it("should only keep segment-boundary matches in boundary mode", async () => {
+			const { fuzzyMatchFiles } = await import("../file-search")
+			const items = [
+				file("src/status.rs"),
+				file("docs/usage/setup.md"),
+				file("lib/deep/nested/user_service.rs"),
+				file("src/user_service.rs"),
+				file("src/UserService.ts"),
+			]
+
+			const results = fuzzyMatchFiles(items, "sus", 20, { matchBoundariesOnly: true }).map((item) => item.path)
+
+			expect(results).toEqual(expect.arrayContaining(["src/user_service.rs", "src/UserService.ts"]))
+			expect(results).not.toContain("src/status.rs")
+			expect(results).not.toContain("docs/usage/setup.md")
+			expect(results).not.toContain("lib/deep/nested/user_service.rs")
+		})
+	})
 })
@@ -138,10 +138,88 @@ export async function executeRipgrepForFiles(
 	return executeRipgrep({ args, workspacePath, limit: effectiveLimit })
 }
 
+export type FuzzySearchOptions = {
+	/** Drop results whose fzf score is below this threshold */
+	minScore?: number
+	/**
+	 * Only keep results where the query matches characters at path segment
+	 * boundaries (start of a segment, after "_", "-" or ".", or a camelCase hump),
+	 * e.g. "sus" for "src/user_service.rs"
+	 */
+	matchBoundariesOnly?: boolean
+}
+
+const BOUNDARY_SEPARATORS = new Set(["/", "\\", "_", "-", ".", " "])
+
+/**
+ * Collects the lowercased characters that start a path segment or word.
+ */
+function getBoundaryChars(filePath: string): string {
+	let boundaryChars = ""
+	for (let i = 0; i < filePath.length; i++) {
+		const char = filePath[i]
+		if (BOUNDARY_SEPARATORS.has(char)) continue
+
+		const prev = filePath[i - 1]
+		const isCamelHump = prev !== undefined && /[a-z0-9]/.test(prev) && /[A-Z]/.test(char)
+		if (prev === undefined || BOUNDARY_SEPARATORS.has(prev) || isCamelHump) {
+			boundaryChars += char.toLowerCase()
+		}
+	}
+	return boundaryChars
+}
+
+function isSubsequence(needle: string, haystack: string): boolean {
+	let index = 0
+	for (const char of haystack) {
+		if (char === needle[index]) index++
+		if (index === needle.length) return true
+	}
+	return needle.length === 0
+}
+
+/**
+ * Ranks items against a query with fzf, applying the optional score threshold
+ * and boundary-only filtering.
+ */
+export function fuzzyMatchFiles(
+	items: FileResult[],
+	query: string,
+	limit: number,
+	options: FuzzySearchOptions = {},
+): FileResult[] {
+	const { minScore, matchBoundariesOnly = false } = options
+
+	let candidates = items
+	if (matchBoundariesOnly) {
+		const needle = query.replace(/\s+/g, "").toLowerCase()
+		candidates = items.filter((item) => isSubsequence(needle, getBoundaryChars(item.path)))
+	}
+
+	// Create search items for all files AND directories
+	const searchItems = candidates.map((item) => ({
+		original: item,
+		searchStr: `${item.path} ${item.label || ""}`,
+	}))
+
+	// Run fzf search on all items
+	const fzf = new Fzf(searchItems, {
+		selector: (item) => item.searchStr,
+		tiebreakers: [byLengthAsc],
+		limit: limit,
+	})
+
+	return fzf
+		.find(query)
+		.filter((result) => minScore === undefined || result.score >= minScore)
+		.map((result) => result.item.original)
+}
+
 export async function searchWorkspaceFiles(
 	query: string,
 	workspacePath: string,
 	limit: number = 20,
+	options: FuzzySearchOptions = {},
 ): Promise<{ path: string; type: "file" | "folder"; label?: string }[]> {
 	try {
 		// Get all files and directories (uses configured limit)
@@ -152,21 +230,8 @@ export async function searchWorkspaceFiles(
 			return allItems.slice(0, limit)
 		}
 
-		// Create search items for all files AND directories
-		const searchItems = allItems.map((item) => ({
-			original: item,
-			searchStr: `${item.path} ${item.label || ""}`,
-		}))
-
-		// Run fzf search on all items
-		const fzf = new Fzf(searchItems, {
-			selector: (item) => item.searchStr,
-			tiebreakers: [byLengthAsc],
-			limit: limit,
-		})
-
 		// Get all matching results from fzf
-		const fzfResults = fzf.find(query).map((result) => result.item.original)
+		const fzfResults = fuzzyMatchFiles(allItems, query, limit, options)
 
 		// Verify types of the shortest results
 		const verifiedResults = await Promise.all(
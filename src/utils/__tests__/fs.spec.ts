@@ -0,0 +1,201 @@
+import * as os from "os"
+import * as path from "path"
+import * as fs from "fs/promises"
+
+import {
+	resolveRealPath,
+	getLastModified,
+	hasChangedSince,
+	filesEqual,
+	touchFile,
+	writeFileAtomic,
+	writeFileAtomicStream,
+} from "../fs"
+
+describe("fs utils", () => {
+	let tmpDir: string
+
+	beforeEach(async () => {
+		// Resolve the temp dir itself, since it may live behind a symlink (e.g. /tmp on macOS)
+		tmpDir = await fs.realpath(await fs.mkdtemp(path.join(os.tmpdir(), "roo-test-fs-")))
+	})
+
+	afterEach(async () => {
+		await fs.rm(tmpDir, { recursive: true, force: true })
+	})
+
+	describe("resolveRealPath", () => {
+		it.skipIf(process.platform === "win32")("should resolve a symlink to its target", async () => {
+			const target = path.join(tmpDir, "target.txt")
+			const link = path.join(tmpDir, "link.txt")
+			await fs.writeFile(target, "content")
+			await fs.symlink(target, link)
+
+			expect(await resolveRealPath(link)).toBe(target)
+		})
+
+		it("should resolve a relative path to an absolute one", async () => {
+			const target = path.join(tmpDir, "target.txt")
+			await fs.writeFile(target, "content")
+
+			expect(await resolveRealPath(path.relative(process.cwd(), target))).toBe(target)
+		})
+
+		it.skipIf(process.platform === "win32")("should throw ENOENT for a broken symlink", async () => {
+			const link = path.join(tmpDir, "broken.txt")
+			await fs.symlink(path.join(tmpDir, "missing.txt"), link)
+
+			await expect(resolveRealPath(link)).rejects.toMatchObject({ code: "ENOENT" })
+		})
+	})
+
+	describe("getLastModified / hasChangedSince", () => {
+		it("should report the mtime in epoch milliseconds", async () => {
+			const filePath = path.join(tmpDir, "file.txt")
+			await fs.writeFile(filePath, "content")
+			const mtime = new Date("2024-01-02T03:04:05.000Z")
+			await fs.utimes(filePath, mtime, mtime)
+
+			expect(await getLastModified(filePath)).toBe(mtime.getTime())
+		})
+
+		it("should flip to true once the file is touched", async () => {
+			const filePath = path.join(tmpDir, "file.txt")
+			await fs.writeFile(filePath, "content")
+			const past = new Date(Date.now() - 60_000)
+			await fs.utimes(filePath, past, past)
+			const checkpoint = Date.now() - 30_000
+
+			expect(await hasChangedSince(filePath, checkpoint)).toBe(false)
+
+			const now = new Date()
+			await fs.utimes(filePath, now, now)
+
+			expect(await hasChangedSince(filePath, checkpoint)).toBe(true)
+		})
+
+		it("should throw ENOENT when the file is gone", async () => {
+			const filePath = path.join(tmpDir, "missing.txt")
+
+			await expect(getLastModified(filePath)).rejects.toMatchObject({ code: "ENOENT" })
+			await expect(hasChangedSince(filePath, 0)).rejects.toMatchObject({ code: "ENOENT" })
+		})
+	})
+
+	describe("touchFile", () => {
+		it("should advance the mtime without changing the content", async () => {
+			const filePath = path.join(tmpDir, "file.txt")
+			await fs.writeFile(filePath, "content")
+			const past = new Date("2020-01-01T00:00:00.000Z")
+			await fs.utimes(filePath, past, past)
+
+			await touchFile(filePath)
+
+			expect(await getLastModified(filePath)).toBeGreaterThan(past.getTime())
+			expect(await fs.readFile(filePath, "utf8")).toBe("content")
+		})
+
+		it("should create a missing file", async () => {
+			const filePath = path.join(tmpDir, "new.txt")
+
+			await touchFile(filePath)
+
+			expect(await fs.readFile(filePath, "utf8")).toBe("")
+		})
+
+		it.skipIf(process.platform === "win32" || process.getuid?.() === 0)(
+			"should throw a permission error for a read-only directory",
+			async () => {
+				const readOnlyDir = path.join(tmpDir, "read-only")
+				await fs.mkdir(readOnlyDir)
+				await fs.chmod(readOnlyDir, 0o555)
+
+				try {
+					await expect(touchFile(path.join(readOnlyDir, "file.txt"))).rejects.toMatchObject({
+						code: "EACCES",
+					})
+				} finally {
+					await fs.chmod(readOnlyDir, 0o755)
+				}
+			},
+		)
+	})
+
+	describe("filesEqual", () => {
+		const writeFile = async (name: string, content: string | Buffer) => {
+			const filePath = path.join(tmpDir, name)
+			await fs.writeFile(filePath, content)
+			return filePath
+		}
+
+		it("should return true for identical files", async () => {
+			const content = Buffer.alloc(200 * 1024, "abc")
+			const a = await writeFile("a.bin", content)
+			const b = await writeFile("b.bin", content)
+
+			expect(await filesEqual(a, b)).toBe(true)
+		})
+
+		it("should return false for same-size files with different content", async () => {
+			const content = Buffer.alloc(200 * 1024, "abc")
+			const changed = Buffer.from(content)
+			changed[changed.length - 1] ^= 0xff
+			const a = await writeFile("a.bin", content)
+			const b = await writeFile("b.bin", changed)
+
+			expect(await filesEqual(a, b)).toBe(false)
+		})
+
+		it("should return false for files of different sizes", async () => {
+			const a = await writeFile("a.txt", "content")
+			const b = await writeFile("b.txt", "content\n")
+
+			expect(await filesEqual(a, b)).toBe(false)
+		})
+
+		it("should return false when a file is missing", async () => {
+			const a = await writeFile("a.txt", "content")
+
+			expect(await filesEqual(a, path.join(tmpDir, "missing.txt"))).toBe(false)
+		})
+
+		it("should return true for two empty files", async () => {
+			const a = await writeFile("a.txt", "")
+			const b = await writeFile("b.txt", "")
+
+			expect(await filesEqual(a, b)).toBe(true)
+		})
+	})
+
+	describe("writeFileAtomic / writeFileAtomicStream", () => {
+		async function* chunksOf(...chunks: string[]) {
+			yield* chunks
+		}
+
+		it("should create a new file", async () => {
+			const filePath = path.join(tmpDir, "new.txt")
+
+			await writeFileAtomic(filePath, "content")
+
+			expect(await fs.readFile(filePath, "utf8")).toBe("content")
+		})
+
+		it.skipIf(process.platform === "win32")("should write through a symlink and keep the link", async () => {
+			const target = path.join(tmpDir, "real.txt")
+			const link = path.join(tmpDir, "link.txt")
+			await fs.writeFile(target, "a\n")
+			await fs.symlink(target, link)
+
+			await writeFileAtomic(link, "b\n")
+
+			expect((await fs.lstat(link)).isSymbolicLink()).toBe(true)
+			expect(await fs.readFile(target, "utf8")).toBe("b\n")
+
+			await writeFileAtomicStream(link, chunksOf("c", "\n"))
+
+			expect((await fs.lstat(link)).isSymbolicLink()).toBe(true)
+			expect(await fs.readFile(target, "utf8")).toBe("c\n")
+			expect((await fs.readdir(tmpDir)).sort()).toEqual(["link.txt", "real.txt"])
+		})
+	})
+})
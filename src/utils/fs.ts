@@ -1,4 +1,4 @@
-import fs from "fs/promises"
+import fs, { type FileHandle } from "fs/promises"
 import * as path from "path"
 
 /**
@@ -45,3 +45,221 @@ export async function fileExistsAtPath(filePath: string): Promise<boolean> {
 		return false
 	}
 }
+
+/**
+ * Writes content to a file atomically by writing a temporary file in the same
+ * directory and renaming it over the target. The target's permissions are kept,
+ * and symlinks are followed so the link itself survives.
+ *
+ * @param filePath - The path of the file to write.
+ * @param content - The content to write.
+ */
+export async function writeFileAtomic(filePath: string, content: string | Buffer): Promise<void> {
+	const targetPath = await resolveWriteTarget(filePath)
+	const tempPath = getAtomicTempPath(targetPath)
+
+	try {
+		const mode = await getFileMode(targetPath)
+
+		await fs.writeFile(tempPath, content, mode !== undefined ? { mode } : undefined)
+		await fs.rename(tempPath, targetPath)
+	} catch (error) {
+		await fs.rm(tempPath, { force: true }).catch(() => {})
+		throw error
+	}
+}
+
+/**
+ * Like writeFileAtomic, but writes content as it is produced so the whole file
+ * never has to be held in memory.
+ *
+ * @param filePath - The path of the file to write.
+ * @param chunks - The content to write, in order.
+ * @param shouldCommit - Optional. Called once every chunk is written; returning false discards the
+ * temporary file and leaves the target untouched.
+ */
+export async function writeFileAtomicStream(
+	filePath: string,
+	chunks: AsyncIterable<string | Buffer>,
+	shouldCommit?: () => boolean,
+): Promise<void> {
+	const targetPath = await resolveWriteTarget(filePath)
+	const tempPath = getAtomicTempPath(targetPath)
+
+	try {
+		const handle = await fs.open(tempPath, "w", await getFileMode(targetPath))
+		try {
+			for await (const chunk of chunks) {
+				await handle.write(chunk)
+			}
+		} finally {
+			await handle.close()
+		}
+
+		if (shouldCommit && !shouldCommit()) {
+			await fs.rm(tempPath, { force: true })
+			return
+		}
+		await fs.rename(tempPath, targetPath)
+	} catch (error) {
+		await fs.rm(tempPath, { force: true }).catch(() => {})
+		throw error
+	}
+}
+
+/**
+ * Resolves symlinks so an atomic write replaces the link's target rather than the link.
+ * Paths that don't exist yet are written as given.
+ */
+async function resolveWriteTarget(filePath: string): Promise<string> {
+	return fs.realpath(filePath).catch(() => filePath)
+}
+
+/**
+ * Builds a temporary path next to the target, so the final rename stays on one filesystem.
+ */
+function getAtomicTempPath(filePath: string): string {
+	return path.join(path.dirname(filePath), `.${path.basename(filePath)}.${process.pid}.${Date.now()}.tmp`)
+}
+
+/**
+ * Gets a file's permission bits, or undefined if it doesn't exist yet.
+ */
+async function getFileMode(filePath: string): Promise<number | undefined> {
+	return fs
+		.stat(filePath)
+		.then((stats) => stats.mode)
+		.catch(() => undefined)
+}
+
+/**
+ * Rethrows a missing-file error with a consistent "ENOENT" code and message.
+ */
+function rethrowNotFound(error: unknown, filePath: string): never {
+	if ((error as NodeJS.ErrnoException).code === "ENOENT") {
+		throw Object.assign(new Error(`File not found: ${filePath}`), { code: "ENOENT" })
+	}
+	throw error
+}
+
+/**
+ * Resolves a path to its canonical absolute form, following every symlink.
+ * Reads through fs already follow symlinks; use this when the target's own path is needed.
+ *
+ * @param filePath - The path to resolve.
+ * @returns A promise that resolves to the canonical path.
+ * @throws {Error} With code "ENOENT" when the path or a link target does not exist.
+ */
+export async function resolveRealPath(filePath: string): Promise<string> {
+	try {
+		return await fs.realpath(filePath)
+	} catch (error) {
+		rethrowNotFound(error, filePath)
+	}
+}
+
+/**
+ * Gets a file's last modification time without reading its content.
+ *
+ * @param filePath - The path of the file.
+ * @returns A promise that resolves to the mtime in epoch milliseconds.
+ * @throws {Error} With code "ENOENT" when the file does not exist.
+ */
+export async function getLastModified(filePath: string): Promise<number> {
+	try {
+		return (await fs.stat(filePath)).mtimeMs
+	} catch (error) {
+		rethrowNotFound(error, filePath)
+	}
+}
+
+/**
+ * Checks whether a file was modified after a given time, for cheap staleness polling.
+ *
+ * @param filePath - The path of the file.
+ * @param sinceMs - The reference time in epoch milliseconds.
+ * @returns A promise that resolves to true if the file's mtime is later than `sinceMs`.
+ * @throws {Error} With code "ENOENT" when the file does not exist.
+ */
+export async function hasChangedSince(filePath: string, sinceMs: number): Promise<boolean> {
+	return (await getLastModified(filePath)) > sinceMs
+}
+
+/**
+ * Sets a file's modification and access times to now, creating an empty file
+ * if it doesn't exist. Existing content is left untouched.
+ *
+ * @param filePath - The path of the file.
+ * @returns A promise that resolves once the timestamps are updated.
+ * @throws {Error} With code "EACCES" or "EPERM" when the file can't be created or updated.
+ */
+export async function touchFile(filePath: string): Promise<void> {
+	try {
+		// Append mode creates a missing file without truncating an existing one
+		const handle = await fs.open(filePath, "a")
+		await handle.close()
+
+		const now = new Date()
+		await fs.utimes(filePath, now, now)
+	} catch (error) {
+		const code = (error as NodeJS.ErrnoException).code
+		if (code === "EACCES" || code === "EPERM") {
+			throw Object.assign(new Error(`Permission denied: ${filePath}`), { code })
+		}
+		throw error
+	}
+}
+
+/**
+ * Compares two files byte for byte. Files of different sizes are rejected from
+ * their stats alone; otherwise both are read in chunks, stopping at the first
+ * difference.
+ *
+ * @param pathA - The path of the first file.
+ * @param pathB - The path of the second file.
+ * @returns A promise that resolves to true if both files exist and have identical content.
+ */
+export async function filesEqual(pathA: string, pathB: string): Promise<boolean> {
+	const stats = await Promise.all([fs.stat(pathA), fs.stat(pathB)]).catch(() => undefined)
+	if (!stats) {
+		return false
+	}
+
+	const [statsA, statsB] = stats
+	if (!statsA.isFile() || !statsB.isFile() || statsA.size !== statsB.size) {
+		return false
+	}
+
+	// Opened one at a time so the first handle is still closed if the second open fails
+	const handleA = await fs.open(pathA, "r")
+	try {
+		const handleB = await fs.open(pathB, "r")
+		try {
+			return await handlesEqual(handleA, handleB, statsA.size)
+		} finally {
+			await handleB.close()
+		}
+	} finally {
+		await handleA.close()
+	}
+}
+
+/**
+ * Compares the first `size` bytes of two open files in chunks, stopping at the first difference.
+ */
+async function handlesEqual(handleA: FileHandle, handleB: FileHandle, size: number): Promise<boolean> {
+	const chunkSize = 64 * 1024
+	const bufferA = Buffer.alloc(chunkSize)
+	const bufferB = Buffer.alloc(chunkSize)
+
+	for (let position = 0; position < size; position += chunkSize) {
+		const [{ bytesRead: readA }, { bytesRead: readB }] = await Promise.all([
+			handleA.read(bufferA, 0, chunkSize, position),
+			handleB.read(bufferB, 0, chunkSize, position),
+		])
+		if (readA !== readB || !bufferA.subarray(0, readA).equals(bufferB.subarray(0, readB))) {
+			return false
+		}
+	}
+	return true
+}
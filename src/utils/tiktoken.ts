@@ -6,6 +6,16 @@ const TOKEN_FUDGE_FACTOR = 1.5
 
 let encoder: Tiktoken | null = null
 
+/**
+ * Lazily creates and caches the shared encoder.
+ */
+function getEncoder(): Tiktoken {
+	if (!encoder) {
+		encoder = new Tiktoken(o200kBase.bpe_ranks, o200kBase.special_tokens, o200kBase.pat_str)
+	}
+	return encoder
+}
+
 /**
  * Serializes a tool_use block to text for token counting.
  * Approximates how the API sees the tool call.
@@ -58,11 +68,7 @@ export async function tiktoken(content: Anthropic.Messages.ContentBlockParam[]):
 	}
 
 	let totalTokens = 0
-
-	// Lazily create and cache the encoder if it doesn't exist.
-	if (!encoder) {
-		encoder = new Tiktoken(o200kBase.bpe_ranks, o200kBase.special_tokens, o200kBase.pat_str)
-	}
+	const tokenizer = getEncoder()
 
 	// Process each content block using the cached encoder.
 	for (const block of content) {
@@ -70,7 +76,7 @@ export async function tiktoken(content: Anthropic.Messages.ContentBlockParam[]):
 			const text = block.text || ""
 
 			if (text.length > 0) {
-				const tokens = encoder.encode(text, undefined, [])
+				const tokens = tokenizer.encode(text, undefined, [])
 				totalTokens += tokens.length
 			}
 		} else if (block.type === "image") {
@@ -87,14 +93,14 @@ export async function tiktoken(content: Anthropic.Messages.ContentBlockParam[]):
 			// Serialize tool_use block to text and count tokens
 			const serialized = serializeToolUse(block as Anthropic.Messages.ToolUseBlockParam)
 			if (serialized.length > 0) {
-				const tokens = encoder.encode(serialized, undefined, [])
+				const tokens = tokenizer.encode(serialized, undefined, [])
 				totalTokens += tokens.length
 			}
 		} else if (block.type === "tool_result") {
 			// Serialize tool_result block to text and count tokens
 			const serialized = serializeToolResult(block as Anthropic.Messages.ToolResultBlockParam)
 			if (serialized.length > 0) {
-				const tokens = encoder.encode(serialized, undefined, [])
+				const tokens = tokenizer.encode(serialized, undefined, [])
 				totalTokens += tokens.length
 			}
 		}
@@ -104,3 +110,15 @@ export async function tiktoken(content: Anthropic.Messages.ContentBlockParam[]):
 	// accurate.
 	return Math.ceil(totalTokens * TOKEN_FUDGE_FACTOR)
 }
+
+/**
+ * Counts BPE tokens in plain text using the shared encoder. Unlike tiktoken(),
+ * no fudge factor is applied, so counts of separate pieces of text add up.
+ */
+export function countTextTokens(text: string): number {
+	if (text.length === 0) {
+		return 0
+	}
+
+	return getEncoder().encode(text, undefined, []).length
+}
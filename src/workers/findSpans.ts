@@ -0,0 +1,9 @@
+import workerpool from "workerpool"
+
+import { scanSpans as scan, type SpanScan } from "../integrations/misc/text-search"
+
+function scanSpans(content: string, source: string, flags: string, byteOffsets: boolean, deadline: number): SpanScan {
+	return scan(content, source, flags, byteOffsets, deadline, (spans) => workerpool.workerEmit(spans))
+}
+
+workerpool.worker({ scanSpans })